@@ -0,0 +1,517 @@
+//! Attribute macros for [`xterm-js-sys`].
+//!
+//! Currently this is just [`xterm_interface`], an alternative to the
+//! `interface!` macro. Use these through `xterm-js-sys` (with the `macros`
+//! feature enabled) rather than depending on this crate directly.
+//!
+//! [`xterm-js-sys`]: https://docs.rs/xterm-js-sys
+
+#![forbid(
+    const_err,
+    dead_code,
+    improper_ctypes,
+    non_shorthand_field_patterns,
+    no_mangle_generic_items,
+    overflowing_literals,
+    path_statements,
+    patterns_in_fns_without_body,
+    private_in_public,
+    unconditional_recursion,
+    unused_allocation,
+    unused_lifetimes,
+    unused_comparisons,
+    unused_parens,
+    while_true
+)]
+#![deny(
+    unused,
+    bad_style,
+    missing_debug_implementations,
+    missing_docs,
+    unsafe_code,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    rust_2018_idioms,
+    variant_size_differences
+)]
+#![deny(clippy::all, clippy::pedantic, clippy::cargo)]
+#![deny(clippy::missing_docs_in_private_items)]
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{
+    parenthesized, parse_macro_input, Attribute, Block, FnArg, Ident,
+    ItemTrait, Pat, Path, ReturnType, Token, TraitItem, TraitItemMethod, Type,
+};
+
+/// Mirrors a JS interface as a Rust trait.
+///
+/// This produces the same things the `interface!` macro does (the trait, the
+/// glue that turns implementations of the trait into JS objects, the blanket
+/// impl for JS implementations of the interface and the `IntoJsInterface`
+/// impl) from a regular trait definition:
+///
+/// ```rust,ignore
+/// #[xterm_interface(mirrors = TerminalAddon, extends(Disposable = XtermDisposable))]
+/// pub trait XtermAddon {
+///     /// This is called when the addon is activated.
+///     fn activate(&mut self, terminal: Terminal);
+/// }
+/// ```
+///
+/// Arguments:
+///   - `mirrors = Type`: the `wasm-bindgen` type for the JS interface; this
+///     has to be a single identifier, so import it first
+///   - `extends(JsType = RustTrait, ..)`: interfaces that the JS interface
+///     extends, along with their Rust duals (optional)
+///   - `crate = path`: where to find `xterm-js-sys` (optional; defaults to
+///     `::xterm_js_sys`)
+///
+/// Members of the trait are methods that take `&self` or `&mut self` (the
+/// Rust value is kept in a `RefCell`; re-entering an object while one of its
+/// `&mut self` methods is running throws a JS error). Methods can have a
+/// default body. In addition:
+///   - `#[getter]` methods (no arguments) become JS getters
+///   - `#[optional]` methods become associated consts that hold an
+///     `Option<fn(&Self, ..)>`; the member is only put on the JS object when
+///     it's `Some`
+///   - `async fn`s become methods that return a `LocalBoxFuture` in Rust and
+///     a `Promise` in JS
+///
+/// As with `interface!` an `IntoJsInterface` trait needs to be in scope.
+#[proc_macro_attribute]
+pub fn xterm_interface(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as Args);
+    let item = parse_macro_input!(item as ItemTrait);
+
+    match expand(&args, item) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// The arguments given to [`xterm_interface`].
+struct Args {
+    /// The JS interface being mirrored.
+    mirrors: Ident,
+    /// `(JS interface, Rust trait)` pairs for the interfaces that are
+    /// extended.
+    extends: Vec<(Path, Path)>,
+    /// The path to `xterm-js-sys`.
+    krate: Path,
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let mut mirrors = None;
+        let mut extends = None;
+        let mut krate = None;
+
+        while !input.is_empty() {
+            if input.peek(Token![crate]) {
+                let key: Token![crate] = input.parse()?;
+                let _: Token![=] = input.parse()?;
+                set_once(&mut krate, input.parse()?, key.span)?;
+            } else {
+                let key: Ident = input.parse()?;
+
+                if key == "mirrors" {
+                    let _: Token![=] = input.parse()?;
+                    let path: Path = input.parse()?;
+                    let ident = path.get_ident().cloned().ok_or_else(|| {
+                        syn::Error::new_spanned(
+                            &path,
+                            "`mirrors` takes the name of the JS interface \
+                            type (a single identifier); import it first",
+                        )
+                    })?;
+
+                    set_once(&mut mirrors, ident, key.span())?;
+                } else if key == "extends" {
+                    let content;
+                    let _ = parenthesized!(content in input);
+                    let pairs =
+                        Punctuated::<Extends, Token![,]>::parse_terminated(
+                            &content,
+                        )?;
+                    let pairs =
+                        pairs.into_iter().map(|e| (e.js, e.rust)).collect();
+
+                    set_once(&mut extends, pairs, key.span())?;
+                } else {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        "unknown argument; expected `mirrors`, `extends` or \
+                        `crate`",
+                    ));
+                }
+            }
+
+            if !input.is_empty() {
+                let _: Token![,] = input.parse()?;
+            }
+        }
+
+        Ok(Self {
+            mirrors: mirrors.ok_or_else(|| {
+                syn::Error::new(
+                    Span::call_site(),
+                    "missing the JS interface to mirror (`mirrors = Type`)",
+                )
+            })?,
+            extends: extends.unwrap_or_default(),
+            krate: krate.unwrap_or_else(|| syn::parse_quote!(::xterm_js_sys)),
+        })
+    }
+}
+
+/// Stores an argument, complaining if it was already given.
+fn set_once<T>(slot: &mut Option<T>, value: T, span: Span) -> syn::Result<()> {
+    if slot.is_some() {
+        return Err(syn::Error::new(span, "this argument was already given"));
+    }
+
+    *slot = Some(value);
+    Ok(())
+}
+
+/// A `JsInterface = RustTrait` pair in `extends(..)`.
+struct Extends {
+    /// The JS interface that's extended.
+    js: Path,
+    /// Its Rust dual.
+    rust: Path,
+}
+
+impl Parse for Extends {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let js = input.parse()?;
+        let _: Token![=] = input.parse()?;
+        let rust = input.parse()?;
+
+        Ok(Self { js, rust })
+    }
+}
+
+/// The kinds of members an interface can have.
+enum Kind {
+    /// A regular method.
+    Method,
+    /// A property, read with a getter.
+    Getter,
+    /// A method that JS objects don't have to have.
+    Optional,
+    /// A method that returns a `Promise`; holds the output type.
+    Promise(Box<Type>),
+}
+
+/// A member of the interface, checked and ready to be handed to `interface!`.
+struct Member {
+    /// What kind of member this is.
+    kind: Kind,
+    /// Attributes (including doc comments) to pass along.
+    attrs: Vec<Attribute>,
+    /// Whether the method takes `&mut self`.
+    mutable: bool,
+    /// The method's `self`.
+    receiver: Token![self],
+    /// The method's name.
+    name: Ident,
+    /// The method's arguments (excluding the receiver).
+    args: Vec<(Ident, Type)>,
+    /// The method's return type, if it has one.
+    ret: Option<Type>,
+    /// The method's default body, if it has one.
+    body: Option<Block>,
+}
+
+/// Collects errors so that they can all be reported at once.
+#[derive(Default)]
+struct Errors(Option<syn::Error>);
+
+impl Errors {
+    /// Records an error.
+    fn push(&mut self, err: syn::Error) {
+        match &mut self.0 {
+            Some(errors) => errors.combine(err),
+            None => self.0 = Some(err),
+        }
+    }
+
+    /// Records an error spanning `tokens`.
+    fn spanned<T: ToTokens>(&mut self, tokens: T, msg: &str) {
+        self.push(syn::Error::new_spanned(tokens, msg));
+    }
+
+    /// Returns the recorded errors, if there are any.
+    fn finish(self) -> syn::Result<()> {
+        self.0.map_or(Ok(()), Err)
+    }
+}
+
+/// Checks the trait and turns it into an `interface!` invocation.
+fn expand(args: &Args, item: ItemTrait) -> syn::Result<TokenStream2> {
+    let mut errors = Errors::default();
+
+    if let Some(unsafety) = &item.unsafety {
+        errors.spanned(unsafety, "interface traits can't be `unsafe`");
+    }
+    if let Some(auto) = &item.auto_token {
+        errors.spanned(auto, "interface traits can't be `auto`");
+    }
+    if !item.generics.params.is_empty() || item.generics.where_clause.is_some()
+    {
+        errors.spanned(
+            &item.generics,
+            "interface traits can't be generic or have lifetimes: \
+            implementations are turned into JS objects and JS types get a \
+            blanket impl, neither of which can be generic",
+        );
+    }
+    if !item.supertraits.is_empty() {
+        errors.spanned(
+            &item.supertraits,
+            "use `extends(JsInterface = RustTrait)` instead of supertraits",
+        );
+    }
+
+    let members: Vec<Member> = item
+        .items
+        .into_iter()
+        .filter_map(|member| match member {
+            TraitItem::Method(method) => member_from(method, &mut errors),
+            other => {
+                errors.spanned(
+                    other,
+                    "only methods can be mirrored; use an extension trait \
+                    for anything else",
+                );
+                None
+            }
+        })
+        .collect();
+
+    errors.finish()?;
+
+    let Args {
+        mirrors,
+        extends,
+        krate,
+    } = args;
+    let (js_parents, rust_parents): (Vec<_>, Vec<_>) =
+        extends.iter().cloned().unzip();
+    let attrs = &item.attrs;
+    let vis = &item.vis;
+    let name = &item.ident;
+
+    let members = members.iter().map(|member| {
+        let kind = match &member.kind {
+            Kind::Method => quote!(method),
+            Kind::Getter => quote!(getter),
+            Kind::Optional => quote!(optional),
+            Kind::Promise(out) => quote!(promise #out),
+        };
+        let attrs = &member.attrs;
+        let mutable = if member.mutable {
+            Some(quote!(mut))
+        } else {
+            None
+        };
+        let receiver = &member.receiver;
+        let name = &member.name;
+        let (arg_names, arg_types): (Vec<_>, Vec<_>) =
+            member.args.iter().cloned().unzip();
+        let ret = match (&member.kind, &member.ret) {
+            (Kind::Promise(out), _) => {
+                Some(quote!(#krate::ext::LocalBoxFuture<#out>))
+            }
+            (_, ret) => ret.as_ref().map(ToTokens::to_token_stream),
+        };
+        let body = &member.body;
+
+        quote! {
+            {
+                [#kind] [#(#attrs)*] [#mutable] [#receiver]
+                #name (#(#arg_names: #arg_types),*) [#ret] [#body]
+            }
+        }
+    });
+
+    Ok(quote! {
+        #krate::interface! {
+            @generate
+            [#(#attrs)*] [#vis] [#name] [#mirrors]
+            [#(#js_parents as #rust_parents),*]
+            [#(#members)*]
+        }
+    })
+}
+
+/// Checks a method of the trait, turning it into a [`Member`].
+///
+/// Problems are added to `errors`.
+#[allow(clippy::too_many_lines)]
+fn member_from(method: TraitItemMethod, errors: &mut Errors) -> Option<Member> {
+    let TraitItemMethod {
+        attrs,
+        sig,
+        default,
+        ..
+    } = method;
+
+    let mut getter = None;
+    let mut optional = None;
+    let attrs = attrs
+        .into_iter()
+        .filter(|attr| {
+            if attr.path.is_ident("getter") {
+                getter = Some(attr.clone());
+            } else if attr.path.is_ident("optional") {
+                optional = Some(attr.clone());
+            } else {
+                return true;
+            }
+
+            if !attr.tokens.is_empty() {
+                errors.spanned(attr, "this attribute takes no arguments");
+            }
+            false
+        })
+        .collect();
+
+    if let Some(constness) = &sig.constness {
+        errors.spanned(constness, "mirrored methods can't be `const`");
+    }
+    if let Some(unsafety) = &sig.unsafety {
+        errors.spanned(unsafety, "mirrored methods can't be `unsafe`");
+    }
+    if let Some(abi) = &sig.abi {
+        errors.spanned(abi, "mirrored methods can't have an ABI");
+    }
+    if let Some(variadic) = &sig.variadic {
+        errors.spanned(variadic, "mirrored methods can't be variadic");
+    }
+    if !sig.generics.params.is_empty() || sig.generics.where_clause.is_some() {
+        errors.spanned(
+            &sig.generics,
+            "mirrored methods can't be generic or have lifetimes",
+        );
+    }
+
+    let ret = match &sig.output {
+        ReturnType::Default => None,
+        ReturnType::Type(_, ty) => Some((**ty).clone()),
+    };
+
+    let kind = match (&sig.asyncness, getter, optional) {
+        (None, None, None) => Kind::Method,
+        (None, Some(_), None) => Kind::Getter,
+        (None, None, Some(_)) => Kind::Optional,
+        (Some(_), None, None) => {
+            if let Some(out) = &ret {
+                Kind::Promise(Box::new(out.clone()))
+            } else {
+                errors.spanned(
+                    &sig,
+                    "async members must return something that implements \
+                    `PromiseOutput` (i.e. `Result<JsValue, JsValue>`)",
+                );
+                return None;
+            }
+        }
+        _ => {
+            errors.spanned(
+                &sig,
+                "a member can only be one of: `async`, `#[getter]` or \
+                `#[optional]`",
+            );
+            return None;
+        }
+    };
+
+    let mut inputs = sig.inputs.iter();
+    let recv = if let Some(FnArg::Receiver(recv)) = inputs.next() {
+        recv
+    } else {
+        errors.spanned(
+            &sig.ident,
+            "mirrored methods must take `&self` or `&mut self`",
+        );
+        return None;
+    };
+    let (mutable, receiver) = match &recv.reference {
+        Some((_, None)) => (recv.mutability.is_some(), recv.self_token),
+        Some((_, Some(lifetime))) => {
+            errors.spanned(lifetime, "mirrored methods can't have lifetimes");
+            return None;
+        }
+        None => {
+            errors.spanned(
+                recv,
+                "mirrored methods must take `&self` or `&mut self`",
+            );
+            return None;
+        }
+    };
+
+    let args = inputs
+        .filter_map(|arg| match arg {
+            FnArg::Typed(arg) => match &*arg.pat {
+                Pat::Ident(pat)
+                    if pat.by_ref.is_none()
+                        && pat.mutability.is_none()
+                        && pat.subpat.is_none() =>
+                {
+                    Some((pat.ident.clone(), (*arg.ty).clone()))
+                }
+                pat => {
+                    errors.spanned(pat, "arguments must be plain identifiers");
+                    None
+                }
+            },
+            FnArg::Receiver(recv) => {
+                errors.spanned(recv, "unexpected receiver");
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if matches!(kind, Kind::Getter) && (!args.is_empty() || ret.is_none()) {
+        errors.spanned(
+            &sig,
+            "getters take no arguments and return the property's value",
+        );
+    }
+
+    if mutable && !matches!(kind, Kind::Method) {
+        errors.spanned(
+            &sig,
+            "only plain methods can take `&mut self`; use `&self` here",
+        );
+    }
+    if default.is_some() && !matches!(kind, Kind::Method) {
+        errors.spanned(
+            &sig,
+            "only plain methods can have a default body (optional members \
+            default to `None`)",
+        );
+    }
+
+    Some(Member {
+        kind,
+        attrs,
+        mutable,
+        receiver,
+        name: sig.ident,
+        args,
+        ret,
+        body: default,
+    })
+}
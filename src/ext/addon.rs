@@ -2,8 +2,22 @@
 //!
 //! [Addons]: crate::xterm::TerminalAddon
 
-use super::disposable::XtermDisposable;
-use super::{interface, Disposable, IntoJsInterface, Terminal, TerminalAddon};
+use super::disposable::{DisposableStore, XtermDisposable};
+use super::keyboard::CsiHandler;
+use super::{
+    calculated_doc, interface, Disposable, EventListener, IntoJsInterface,
+    Terminal, TerminalAddon,
+};
+use crate::xterm::{
+    FunctionIdentifier, KeyEventData, RenderEventData, ResizeEventData, Str,
+};
+use crate::ReadOnlyArray;
+
+use wasm_bindgen::{prelude::Closure, JsValue};
+
+use core::cell::{Cell, Ref, RefCell, RefMut};
+use core::fmt::{self, Debug};
+use std::rc::Rc;
 
 interface! {
     #[allow(clippy::module_name_repetitions)]
@@ -23,6 +37,9 @@ impl Terminal {
     /// [JS defined addons](TerminalAddon) and is otherwise identical to
     /// [`Terminal::load_addon`].
     ///
+    /// Rust addons are converted with [`XtermAddon::into_js`]: the copy of the
+    /// addon that's handed to xterm.js is freed when the addon is disposed of.
+    ///
     /// [addon]: TerminalAddon
     pub fn load_xterm_addon<A: IntoJsInterface<TerminalAddon>>(
         &self,
@@ -31,3 +48,342 @@ impl Terminal {
         self.load_addon(addon.by_ref())
     }
 }
+
+/// An addon written in Rust.
+///
+/// This is a higher level alternative to [`XtermAddon`]: rather than a raw
+/// [`Terminal`], [`activate`] is given an [`AddonContext`] and everything that's
+/// registered through the context (event listeners, parser hooks, guards) is
+/// disposed of automatically when the addon is.
+///
+/// Addons are loaded with [`Terminal::load_rust_addon`].
+///
+/// [`activate`]: RustAddon::activate
+pub trait RustAddon: 'static {
+    /// This is called when the addon is loaded into a terminal.
+    fn activate(&mut self, ctx: &AddonContext);
+
+    /// This is called when the addon is disposed of, before the registrations
+    /// made through its [`AddonContext`] are.
+    ///
+    /// Does nothing by default.
+    fn dispose(&mut self) {}
+}
+
+/// Handed to [`RustAddon::activate`]; registers things on behalf of an addon.
+///
+/// Everything registered through the context is disposed of when the addon is
+/// (things that are registered after that are disposed of immediately). The
+/// context can be cloned and kept around to register things later on.
+#[derive(Debug, Clone)]
+pub struct AddonContext {
+    /// The terminal the addon was loaded into.
+    terminal: Terminal,
+    /// The addon's registrations.
+    store: Rc<DisposableStore>,
+}
+
+/// Generates the [`AddonContext`] methods for events on [`Terminal`].
+macro_rules! context_events {
+    ($(
+        @doc: $kind:literal
+        $nom:ident: ($($arg:ty)?) => $listener:ident
+    )*) => {$(
+        calculated_doc! {
+            #[doc = $crate::ext::_m_sprt::concat!(
+                " Attaches a ",
+                    $kind,
+                " event listener that's removed when the addon is\n",
+                " disposed of.\n",
+                "\n",
+                " See [`Terminal::",
+                    $crate::ext::_m_sprt::stringify!($listener),
+                "`].",
+            )]
+            >>>
+            pub fn $nom<F>(&self, listener: F)
+            where
+                F: FnMut($($arg)?),
+                F: 'static,
+            {
+                self.store.add_guard(self.terminal.$listener(listener));
+            }
+        }
+    )*};
+}
+
+/// An `ESC` handler, as taken by [`Parser::register_esc_handler`].
+///
+/// [`Parser::register_esc_handler`]: crate::xterm::Parser::register_esc_handler
+type EscHandler = dyn FnMut() -> bool;
+
+/// An `OSC` handler, as taken by [`Parser::register_osc_handler`].
+///
+/// [`Parser::register_osc_handler`]: crate::xterm::Parser::register_osc_handler
+type OscHandler = dyn FnMut(Str) -> bool;
+
+/// A `DCS` handler, as taken by [`Parser::register_dcs_handler`].
+///
+/// [`Parser::register_dcs_handler`]: crate::xterm::Parser::register_dcs_handler
+type DcsHandler = dyn FnMut(Str, ReadOnlyArray<JsValue>) -> bool;
+
+impl AddonContext {
+    /// The terminal the addon was loaded into.
+    #[must_use]
+    pub fn terminal(&self) -> &Terminal {
+        &self.terminal
+    }
+
+    /// The store that holds the addon's registrations.
+    ///
+    /// Anything added to it is disposed of along with the addon.
+    #[must_use]
+    pub fn store(&self) -> &DisposableStore {
+        &self.store
+    }
+
+    /// Keeps `guard` (i.e. an [`EventListener`] or a [`DisposableWrapper`])
+    /// alive until the addon is disposed of.
+    pub fn track<G: 'static>(&self, guard: G) {
+        self.store.add_guard(guard);
+    }
+
+    #[rustfmt::skip]
+    context_events! {
+        @doc: "binary"
+        on_binary: (Str) => binary_listener
+
+        @doc: "cursor move"
+        on_cursor_move: () => cursor_move_listener
+
+        @doc: "data"
+        on_data: (Str) => data_listener
+
+        @doc: "key event"
+        on_key: (KeyEventData) => key_listener
+
+        @doc: "line feed"
+        on_line_feed: () => line_feed_listener
+
+        @doc: "render"
+        on_render: (RenderEventData) => render_listener
+
+        @doc: "resize"
+        on_resize: (ResizeEventData) => resize_listener
+
+        @doc: "scroll"
+        on_scroll: (u32) => scroll_listener
+
+        @doc: "selection change"
+        on_selection_change: () => selection_change_listener
+
+        @doc: "title change"
+        on_title_change: (Str) => title_change_listener
+    }
+
+    /// Adds a `CSI` handler that's removed when the addon is disposed of.
+    ///
+    /// See [`Parser::register_csi_handler`].
+    ///
+    /// [`Parser::register_csi_handler`]: crate::xterm::Parser::register_csi_handler
+    pub fn register_csi_handler<F>(&self, id: FunctionIdentifier, handler: F)
+    where
+        F: FnMut(ReadOnlyArray<JsValue>) -> bool,
+        F: 'static,
+    {
+        let handler: Box<CsiHandler> = Box::new(handler);
+        let parser = self.terminal.parser();
+
+        self.track(EventListener::new(Closure::wrap(handler), |closure| {
+            parser.register_csi_handler(id, closure)
+        }));
+    }
+
+    /// Adds a `DCS` handler that's removed when the addon is disposed of.
+    ///
+    /// See [`Parser::register_dcs_handler`].
+    ///
+    /// [`Parser::register_dcs_handler`]: crate::xterm::Parser::register_dcs_handler
+    pub fn register_dcs_handler<F>(&self, id: FunctionIdentifier, handler: F)
+    where
+        F: FnMut(Str, ReadOnlyArray<JsValue>) -> bool,
+        F: 'static,
+    {
+        let handler: Box<DcsHandler> = Box::new(handler);
+        let parser = self.terminal.parser();
+
+        self.track(EventListener::new(Closure::wrap(handler), |closure| {
+            parser.register_dcs_handler(id, closure)
+        }));
+    }
+
+    /// Adds an `ESC` handler that's removed when the addon is disposed of.
+    ///
+    /// See [`Parser::register_esc_handler`].
+    ///
+    /// [`Parser::register_esc_handler`]: crate::xterm::Parser::register_esc_handler
+    pub fn register_esc_handler<F>(&self, id: FunctionIdentifier, handler: F)
+    where
+        F: FnMut() -> bool,
+        F: 'static,
+    {
+        let handler: Box<EscHandler> = Box::new(handler);
+        let parser = self.terminal.parser();
+
+        self.track(EventListener::new(Closure::wrap(handler), |closure| {
+            parser.register_esc_handler(id, closure)
+        }));
+    }
+
+    /// Adds an `OSC` handler that's removed when the addon is disposed of.
+    ///
+    /// See [`Parser::register_osc_handler`].
+    ///
+    /// [`Parser::register_osc_handler`]: crate::xterm::Parser::register_osc_handler
+    pub fn register_osc_handler<F>(&self, ident: u32, handler: F)
+    where
+        F: FnMut(Str) -> bool,
+        F: 'static,
+    {
+        let handler: Box<OscHandler> = Box::new(handler);
+        let parser = self.terminal.parser();
+
+        self.track(EventListener::new(Closure::wrap(handler), |closure| {
+            parser.register_osc_handler(ident, closure)
+        }));
+    }
+}
+
+/// The state a [`RustAddon`] shares with its [`RustAddonHandle`].
+struct AddonState<A> {
+    /// The addon itself.
+    addon: RefCell<A>,
+    /// The registrations made through the addon's [`AddonContext`].
+    store: Rc<DisposableStore>,
+    /// Whether the addon has been disposed of.
+    disposed: Cell<bool>,
+}
+
+impl<A: Debug> Debug for AddonState<A> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("AddonState")
+            .field("addon", &self.addon)
+            .field("store", &self.store)
+            .field("disposed", &self.disposed)
+            .finish()
+    }
+}
+
+/// Adapts a [`RustAddon`] to [`XtermAddon`] so it can be handed to xterm.js.
+struct Adapter<A>(Rc<AddonState<A>>);
+
+impl<A: RustAddon> XtermDisposable for Adapter<A> {
+    fn dispose(&self) {
+        if self.0.disposed.replace(true) {
+            return;
+        }
+
+        self.0.addon.borrow_mut().dispose();
+        self.0.store.dispose();
+    }
+}
+
+impl<A: RustAddon> XtermAddon for Adapter<A> {
+    fn activate(&self, terminal: Terminal) {
+        let ctx = AddonContext {
+            terminal,
+            store: self.0.store.clone(),
+        };
+
+        self.0.addon.borrow_mut().activate(&ctx);
+    }
+}
+
+/// A [`RustAddon`] that has been loaded into a [`Terminal`].
+///
+/// Made by [`Terminal::load_rust_addon`]. Dropping the handle unloads the addon
+/// (if xterm.js hasn't already, i.e. because the terminal was disposed of).
+pub struct RustAddonHandle<A: RustAddon> {
+    /// The JS side of the addon, as handed to xterm.js.
+    js: TerminalAddon,
+    /// The Rust side of the addon.
+    state: Rc<AddonState<A>>,
+}
+
+impl<A: RustAddon + Debug> Debug for RustAddonHandle<A> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("RustAddonHandle")
+            .field("js", &self.js)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<A: RustAddon> RustAddonHandle<A> {
+    /// Borrows the addon.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the addon is mutably borrowed (i.e. if this is called from
+    /// within [`RustAddon::activate`] or [`RustAddon::dispose`]).
+    #[must_use]
+    pub fn addon(&self) -> Ref<'_, A> {
+        self.state.addon.borrow()
+    }
+
+    /// Mutably borrows the addon.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the addon is already borrowed.
+    #[must_use]
+    pub fn addon_mut(&self) -> RefMut<'_, A> {
+        self.state.addon.borrow_mut()
+    }
+
+    /// The JS side of the addon (what was handed to xterm.js).
+    #[must_use]
+    pub fn js_addon(&self) -> &TerminalAddon {
+        &self.js
+    }
+
+    /// Whether the addon has been disposed of.
+    #[must_use]
+    pub fn is_disposed(&self) -> bool {
+        self.state.disposed.get()
+    }
+
+    /// Unloads the addon; this is the same as dropping the handle.
+    pub fn unload(self) {}
+}
+
+impl<A: RustAddon> Drop for RustAddonHandle<A> {
+    fn drop(&mut self) {
+        // This goes through xterm.js (which wraps the addon's `dispose`) so
+        // that the terminal forgets about the addon too.
+        if !self.is_disposed() {
+            self.js.dispose();
+        }
+    }
+}
+
+impl Terminal {
+    /// Loads a [`RustAddon`] into this instance of the xterm.js [`Terminal`].
+    ///
+    /// The returned handle unloads the addon when it's dropped.
+    pub fn load_rust_addon<A: RustAddon>(
+        &self,
+        addon: A,
+    ) -> RustAddonHandle<A> {
+        let state = Rc::new(AddonState {
+            addon: RefCell::new(addon),
+            store: Rc::new(DisposableStore::new()),
+            disposed: Cell::new(false),
+        });
+
+        let js = XtermAddon::into_js(Adapter(state.clone()));
+        self.load_addon(js.clone());
+
+        RustAddonHandle { js, state }
+    }
+}
@@ -0,0 +1,356 @@
+//! A registry for the addons that are loaded into a [`Terminal`].
+//!
+//! [`AddonManager`] loads addons (by way of [`Terminal::load_xterm_addon`]) in
+//! an order that respects the dependencies they declare, gives typed access to
+//! them, and unloads them in the reverse order.
+
+use super::{Terminal, TerminalAddon, XtermAddon, XtermDisposable};
+
+use core::any::Any;
+use core::fmt::{self, Debug, Display};
+use std::error::Error;
+use std::rc::Rc;
+
+/// The parts of an addon that [`AddonManager`] needs, type erased.
+trait ManagedAddon: Any {
+    /// Makes a JS addon to hand to xterm.js.
+    fn to_js(&self) -> TerminalAddon;
+
+    /// The addon itself, for downcasting.
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: XtermAddon + 'static> ManagedAddon for Rc<T> {
+    fn to_js(&self) -> TerminalAddon {
+        XtermAddon::into_js(Shared(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        &**self
+    }
+}
+
+/// Forwards to the addon an [`AddonManager`] holds so that xterm.js activates
+/// (and disposes of) that addon rather than a clone of it.
+struct Shared<A>(Rc<A>);
+
+impl<A> Clone for Shared<A> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<A: XtermAddon> XtermDisposable for Shared<A> {
+    fn dispose(&self) {
+        self.0.dispose();
+    }
+}
+
+impl<A: XtermAddon> XtermAddon for Shared<A> {
+    fn activate(&self, terminal: Terminal) {
+        self.0.activate(terminal);
+    }
+}
+
+/// An addon that's been registered with an [`AddonManager`].
+struct Entry {
+    /// The name the addon was registered under.
+    name: String,
+    /// The names of the addons that need to be loaded before this one.
+    dependencies: Vec<String>,
+    /// The addon.
+    addon: Box<dyn ManagedAddon>,
+    /// The JS addon that was handed to xterm.js, while the addon is loaded.
+    js: Option<TerminalAddon>,
+}
+
+impl Debug for Entry {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Entry")
+            .field("name", &self.name)
+            .field("dependencies", &self.dependencies)
+            .field("addon", &self.addon.as_any())
+            .field("js", &self.js)
+            .finish()
+    }
+}
+
+/// Problems with the addons registered with an [`AddonManager`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(clippy::module_name_repetitions)]
+pub enum AddonError {
+    /// More than one addon was registered under this name.
+    DuplicateName(String),
+    /// An addon depends on an addon that wasn't registered.
+    UnknownDependency {
+        /// The addon with the dependency.
+        addon: String,
+        /// The name of the addon that's missing.
+        dependency: String,
+    },
+    /// These addons (transitively) depend on each other.
+    Cycle(Vec<String>),
+}
+
+impl Display for AddonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddonError::DuplicateName(name) => {
+                write!(f, "more than one addon is named `{}`", name)
+            }
+            AddonError::UnknownDependency { addon, dependency } => write!(
+                f,
+                "addon `{}` depends on `{}`, which isn't registered",
+                addon, dependency
+            ),
+            AddonError::Cycle(names) => write!(
+                f,
+                "addons depend on each other: `{}`",
+                names.join("`, `")
+            ),
+        }
+    }
+}
+
+impl Error for AddonError {}
+
+/// Loads a set of named addons into a [`Terminal`].
+///
+/// Addons are [registered](AddonManager::register) along with the names of the
+/// addons they depend on and are then [loaded](AddonManager::load) so that
+/// every addon is loaded after its dependencies (addons that don't depend on
+/// each other are loaded in the order they were registered in).
+///
+/// Addons are unloaded in the reverse order when the manager is dropped (or
+/// when [`AddonManager::unload_all`] is called).
+///
+/// ```rust,no_run
+/// # use xterm_js_sys::{ext::{AddonManager, XtermAddon, XtermDisposable}, xterm::Terminal};
+/// # #[derive(Clone)] struct Search;
+/// # impl XtermDisposable for Search {}
+/// # impl XtermAddon for Search { fn activate(&self, _: Terminal) {} }
+/// # #[derive(Clone)] struct Snapshot;
+/// # impl XtermDisposable for Snapshot {}
+/// # impl XtermAddon for Snapshot { fn activate(&self, _: Terminal) {} }
+/// # let terminal = Terminal::new(None);
+/// let mut addons = AddonManager::new();
+/// let _ = addons
+///     .register("search", &["snapshot"], Search)
+///     .register("snapshot", &[], Snapshot);
+///
+/// addons.load(&terminal).unwrap();
+/// assert!(addons.get::<Search>().is_some());
+/// ```
+#[derive(Debug, Default)]
+#[allow(clippy::module_name_repetitions)]
+pub struct AddonManager {
+    /// The registered addons, in the order they were registered in.
+    entries: Vec<Entry>,
+    /// Indexes (into `entries`) of the loaded addons, in the order they were
+    /// loaded in.
+    loaded: Vec<usize>,
+}
+
+impl AddonManager {
+    /// Creates a manager with no addons.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers an addon under `name`, to be loaded after the addons named
+    /// in `dependencies`.
+    ///
+    /// The addon isn't loaded until [`AddonManager::load`] is called. The
+    /// manager keeps the addon and hands xterm.js a JS addon that forwards to
+    /// it, so [`AddonManager::get`] gives back the instance that was activated.
+    pub fn register<A>(
+        &mut self,
+        name: &str,
+        dependencies: &[&str],
+        addon: A,
+    ) -> &mut Self
+    where
+        A: XtermAddon + 'static,
+    {
+        self.entries.push(Entry {
+            name: name.to_string(),
+            dependencies: dependencies
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            addon: Box::new(Rc::new(addon)),
+            js: None,
+        });
+
+        self
+    }
+
+    /// The names of the registered addons, in the order they'd be loaded in.
+    ///
+    /// # Errors
+    ///
+    /// Errors if names are used more than once, if an addon depends on one
+    /// that isn't registered, or if addons depend on each other.
+    pub fn load_order(&self) -> Result<Vec<&str>, AddonError> {
+        Ok(self
+            .order()?
+            .into_iter()
+            .map(|idx| self.entries[idx].name.as_str())
+            .collect())
+    }
+
+    /// Loads the registered addons that aren't loaded yet into `terminal`,
+    /// dependencies first.
+    ///
+    /// # Errors
+    ///
+    /// Errors (without loading anything) in the same cases as
+    /// [`AddonManager::load_order`].
+    pub fn load(&mut self, terminal: &Terminal) -> Result<(), AddonError> {
+        for idx in self.order()? {
+            let entry = &mut self.entries[idx];
+            if entry.js.is_some() {
+                continue;
+            }
+
+            // This is `Terminal::load_xterm_addon`, but we hang on to the JS
+            // addon so we can dispose of it later.
+            let js = entry.addon.to_js();
+            terminal.load_addon(js.clone());
+
+            entry.js = Some(js);
+            self.loaded.push(idx);
+        }
+
+        Ok(())
+    }
+
+    /// Gets the first registered addon of type `T`.
+    #[must_use]
+    pub fn get<T: 'static>(&self) -> Option<&T> {
+        self.entries
+            .iter()
+            .find_map(|entry| entry.addon.as_any().downcast_ref())
+    }
+
+    /// Gets the addon registered under `name` if it's of type `T`.
+    #[must_use]
+    pub fn get_named<T: 'static>(&self, name: &str) -> Option<&T> {
+        self.entries
+            .iter()
+            .find(|entry| entry.name == name)
+            .and_then(|entry| entry.addon.as_any().downcast_ref())
+    }
+
+    /// Whether the addon registered under `name` is loaded.
+    #[must_use]
+    pub fn is_loaded(&self, name: &str) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| entry.name == name && entry.js.is_some())
+    }
+
+    /// Unloads the addon registered under `name` along with the loaded addons
+    /// that (transitively) depend on it, in the reverse order they were loaded
+    /// in.
+    ///
+    /// The addons stay registered and can be loaded again.
+    pub fn unload(&mut self, name: &str) {
+        let mut doomed = vec![name];
+        let mut idx = 0;
+        while let Some(&name) = doomed.get(idx) {
+            for entry in &self.entries {
+                if entry.dependencies.iter().any(|d| d == name)
+                    && !doomed.contains(&entry.name.as_str())
+                {
+                    doomed.push(&entry.name);
+                }
+            }
+            idx += 1;
+        }
+
+        let doomed: Vec<usize> = self
+            .loaded
+            .iter()
+            .copied()
+            .filter(|&idx| doomed.contains(&self.entries[idx].name.as_str()))
+            .collect();
+
+        self.loaded.retain(|idx| !doomed.contains(idx));
+        for idx in doomed.into_iter().rev() {
+            Self::unload_entry(&mut self.entries[idx]);
+        }
+    }
+
+    /// Unloads every loaded addon, in the reverse order they were loaded in.
+    ///
+    /// The addons stay registered and can be loaded again.
+    pub fn unload_all(&mut self) {
+        while let Some(idx) = self.loaded.pop() {
+            Self::unload_entry(&mut self.entries[idx]);
+        }
+    }
+
+    /// Disposes of an entry's JS addon (if it has one).
+    fn unload_entry(entry: &mut Entry) {
+        if let Some(js) = entry.js.take() {
+            js.dispose();
+        }
+    }
+
+    /// Indexes of the entries, in the order they should be loaded in.
+    fn order(&self) -> Result<Vec<usize>, AddonError> {
+        let position =
+            |name: &str| self.entries.iter().position(|e| e.name == name);
+
+        for (idx, entry) in self.entries.iter().enumerate() {
+            if position(&entry.name) != Some(idx) {
+                return Err(AddonError::DuplicateName(entry.name.clone()));
+            }
+
+            if let Some(dependency) =
+                entry.dependencies.iter().find(|d| position(d).is_none())
+            {
+                return Err(AddonError::UnknownDependency {
+                    addon: entry.name.clone(),
+                    dependency: dependency.clone(),
+                });
+            }
+        }
+
+        // Repeatedly take the first entry whose dependencies have all been
+        // taken already.
+        let mut order: Vec<usize> = Vec::with_capacity(self.entries.len());
+        while order.len() < self.entries.len() {
+            let next = self.entries.iter().enumerate().position(|(idx, e)| {
+                !order.contains(&idx)
+                    && e.dependencies.iter().all(|d| {
+                        order.iter().any(|&o| self.entries[o].name == *d)
+                    })
+            });
+
+            match next {
+                Some(idx) => order.push(idx),
+                None => {
+                    return Err(AddonError::Cycle(
+                        self.entries
+                            .iter()
+                            .enumerate()
+                            .filter(|(idx, _)| !order.contains(idx))
+                            .map(|(_, e)| e.name.clone())
+                            .collect(),
+                    ))
+                }
+            }
+        }
+
+        Ok(order)
+    }
+}
+
+impl Drop for AddonManager {
+    fn drop(&mut self) {
+        self.unload_all();
+    }
+}
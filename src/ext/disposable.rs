@@ -3,13 +3,18 @@
 //! [`Disposable`]: crate::xterm::Disposable
 
 use super::{
-    interface, object, Disposable, IntoJsInterface, Terminal, TerminalOptions,
+    interface, object, Disposable, InterfaceHandle, IntoJsInterface, Terminal,
+    TerminalOptions,
 };
 
 use js_sys::{Function, Object};
 use wasm_bindgen::{prelude::wasm_bindgen, JsCast};
 
+use core::any::Any;
+use core::cell::{Cell, RefCell};
+use core::fmt::{self, Debug};
 use core::ops::{Deref, DerefMut};
+use std::rc::Rc;
 
 interface! {
     #[allow(clippy::module_name_repetitions)]
@@ -19,7 +24,9 @@ interface! {
         /// This can involve unregistering an event listener or cleaning up
         /// resources or anything else that should happen when an instance is
         /// disposed of.
-        fn dispose(&self);
+        ///
+        /// Does nothing by default.
+        fn dispose(&self) {}
     }
 }
 
@@ -85,6 +92,166 @@ impl<D: XtermDisposable> Drop for DisposableWrapper<D> {
     }
 }
 
+/// The registration of a Rust implementation of an interface (i.e. a link
+/// provider) along with the [`InterfaceHandle`] that backs it.
+///
+/// Disposing of this undoes the registration and then frees the Rust value and
+/// closures behind the JS object. It's usually held in a [`DisposableWrapper`]
+/// so that this happens on [`Drop`].
+pub struct HandleRegistration<I> {
+    /// Undoes the registration.
+    registration: Disposable,
+    /// The registered object; `None` once it's been freed.
+    handle: RefCell<Option<InterfaceHandle<I>>>,
+}
+
+impl<I: Debug> Debug for HandleRegistration<I> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("HandleRegistration")
+            .field("registration", &self.registration)
+            .field("handle", &self.handle)
+            .finish()
+    }
+}
+
+impl<I> HandleRegistration<I> {
+    /// Ties `handle` to `registration`, the [`Disposable`] that undoes the
+    /// registration of the handle's object.
+    pub fn new(registration: Disposable, handle: InterfaceHandle<I>) -> Self {
+        Self {
+            registration,
+            handle: RefCell::new(Some(handle)),
+        }
+    }
+}
+
+impl<I> XtermDisposable for HandleRegistration<I> {
+    fn dispose(&self) {
+        self.registration.dispose();
+
+        let handle = self.handle.borrow_mut().take();
+        drop(handle);
+    }
+}
+
+/// A child [`DisposableStore`], held by its parent.
+///
+/// Disposes of the child when the parent is cleared.
+struct ChildStore(Rc<DisposableStore>);
+
+impl Drop for ChildStore {
+    fn drop(&mut self) {
+        self.0.dispose();
+    }
+}
+
+/// A collection of [`XtermDisposable`]s and guards (i.e. event listeners or
+/// anything else that cleans up after itself on [`Drop`]) that are disposed of
+/// together.
+///
+/// Everything in the store is disposed of in reverse order (most recently
+/// added first) when the store is [cleared](DisposableStore::clear),
+/// [disposed](XtermDisposable::dispose) or dropped. Once a store has been
+/// disposed, anything that's added to it is disposed of immediately.
+///
+/// Stores can be nested with [`DisposableStore::child`]; this is useful for
+/// grouping together the registrations of one part of a feature.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Default)]
+pub struct DisposableStore {
+    /// The disposables and guards, oldest first.
+    items: RefCell<Vec<Box<dyn Any>>>,
+    /// Whether [`XtermDisposable::dispose`] has been called.
+    disposed: Cell<bool>,
+}
+
+impl Debug for DisposableStore {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("DisposableStore")
+            .field("items", &self.items.borrow().len())
+            .field("disposed", &self.disposed.get())
+            .finish()
+    }
+}
+
+impl DisposableStore {
+    /// Creates an empty store.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an [`XtermDisposable`] to the store.
+    pub fn add<D: XtermDisposable + 'static>(&self, disposable: D) {
+        self.add_guard(DisposableWrapper::from(disposable));
+    }
+
+    /// Adds a guard to the store; the guard is dropped when the store is
+    /// cleared.
+    pub fn add_guard<G: 'static>(&self, guard: G) {
+        if self.disposed.get() {
+            drop(guard);
+        } else {
+            self.items.borrow_mut().push(Box::new(guard));
+        }
+    }
+
+    /// Creates a store that is disposed of when this store is cleared.
+    ///
+    /// The child can also be cleared (or disposed of) on its own.
+    #[must_use]
+    pub fn child(&self) -> Rc<DisposableStore> {
+        let child = Rc::new(DisposableStore::new());
+        self.add_guard(ChildStore(child.clone()));
+
+        child
+    }
+
+    /// Disposes of everything in the store, most recently added first.
+    ///
+    /// Unlike [`dispose`](XtermDisposable::dispose), the store can still be
+    /// used afterwards.
+    pub fn clear(&self) {
+        // Take the items out first so that disposing of an item can add to
+        // the store without a double borrow.
+        let mut items = self.items.replace(Vec::new());
+        while let Some(item) = items.pop() {
+            drop(item);
+        }
+    }
+
+    /// The number of things in the store.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.items.borrow().len()
+    }
+
+    /// Whether the store is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.items.borrow().is_empty()
+    }
+
+    /// Whether the store has been disposed of.
+    #[must_use]
+    pub fn is_disposed(&self) -> bool {
+        self.disposed.get()
+    }
+}
+
+impl XtermDisposable for DisposableStore {
+    fn dispose(&self) {
+        self.disposed.set(true);
+        self.clear();
+    }
+}
+
+impl Drop for DisposableStore {
+    fn drop(&mut self) {
+        self.dispose();
+    }
+}
+
 /// A type that satisfies the [`Disposable`] interface and does nothing on
 /// `dispose`.
 ///
@@ -1,13 +1,32 @@
 //! Sugar for methods that take `IEvent`s.
+//!
+//! For each event there are three flavors:
+//!   - `attach_*_event_listener`: registers a callback and leaks it; dropping
+//!     the returned [`DisposableWrapper`] stops the events but the closure is
+//!     never freed
+//!   - `*_listener`: registers a callback and returns an [`EventListener`]
+//!     that, when dropped, stops the events _and_ frees the closure
+//!   - `*_stream`: returns an [`EventStream`] (a [`Stream`] of the events) that
+//!     cleans up after itself in the same way when dropped
 
 use super::{calculated_doc, Disposable, DisposableWrapper};
 use crate::xterm::{
-    Buffer, BufferNamespace, KeyEventData, RenderEventData, ResizeEventData,
-    Str, Terminal,
+    Buffer, BufferNamespace, KeyEventData, Marker, RenderEventData,
+    ResizeEventData, Str, Terminal,
 };
 
+use futures_core::Stream;
+use wasm_bindgen::closure::WasmClosure;
 use wasm_bindgen::prelude::*;
 
+use core::any::Any;
+use core::cell::RefCell;
+use core::fmt::{self, Debug};
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use std::collections::VecDeque;
+use std::rc::Rc;
+
 macro_rules! event_methods {
     ($(
         $(#[$metas:meta])*
@@ -71,6 +90,86 @@ macro_rules! event_method {
     };
 }
 
+/// Like `event_methods`, but generates the `*_listener` and `*_stream`
+/// flavors.
+macro_rules! event_streams {
+    ($(
+        @doc: $kind:literal
+        $vis:vis $listener:ident, $stream:ident: ($($arg:ty)?) => $js_func:path
+    )*) => {$(
+        calculated_doc! {
+            #[doc = $crate::ext::_m_sprt::concat!(
+                " Attaches a ",
+                    $kind,
+                " event listener and returns an [`EventListener`] that\n",
+                " can be dropped to make xterm.js stop sending the event",
+                " listener events\n",
+                " and to free the listener.\n",
+                "\n",
+                " This is sugar for ",
+                "[`",
+                    $crate::ext::_m_sprt::stringify!($js_func),
+                "`].",
+                "\n\n",
+                "  [`",
+                    $crate::ext::_m_sprt::stringify!($js_func),
+                "`]: ",
+                    $crate::ext::_m_sprt::stringify!($js_func),
+            )]
+            >>>
+            $vis fn $listener<F>(
+                &self,
+                listener: F,
+            ) -> EventListener<dyn FnMut($($arg)?)>
+            where
+                F: FnMut($($arg)?),
+                F: 'static,
+            {
+                let listener: Box<dyn FnMut($($arg)?)> = Box::new(listener);
+                EventListener::new(Closure::wrap(listener), |l| $js_func(self, l))
+            }
+        }
+
+        calculated_doc! {
+            #[doc = $crate::ext::_m_sprt::concat!(
+                " Returns an [`EventStream`] of ",
+                    $kind,
+                " events.\n",
+                "\n",
+                " Dropping the stream makes xterm.js stop sending the events",
+                " and frees\n",
+                " the underlying listener.",
+            )]
+            >>>
+            $vis fn $stream(&self) -> EventStream<event_item!($($arg)?)> {
+                EventStream::new(|push| {
+                    self.$listener(event_push!(push $(, $arg)?))
+                })
+            }
+        }
+    )*};
+}
+
+/// The item type of an [`EventStream`] for an event with the given arguments.
+macro_rules! event_item {
+    () => {
+        ()
+    };
+    ($arg:ty) => {
+        $arg
+    };
+}
+
+/// A listener that forwards its arguments to an [`EventStream`]'s queue.
+macro_rules! event_push {
+    ($push:ident) => {
+        move || $push(())
+    };
+    ($push:ident, $arg:ty) => {
+        move |event: $arg| $push(event)
+    };
+}
+
 #[rustfmt::skip]
 impl Terminal { event_methods!{
     @doc: "binary"
@@ -113,3 +212,181 @@ impl BufferNamespace { event_methods! {
     pub attach_buffer_change_event_listener: (Buffer)
         => BufferNamespace::on_buffer_change
 }}
+
+#[rustfmt::skip]
+impl Marker { event_methods! {
+    @doc: "dispose"
+    pub attach_dispose_event_listener: () => Marker::on_dispose
+}}
+
+/// A registered event listener that owns its closure.
+///
+/// Dropping this disposes of the JS listener and then frees the closure.
+pub struct EventListener<F: ?Sized + WasmClosure> {
+    /// Unregisters the listener on drop.
+    ///
+    /// Note: field order matters here! Fields are dropped in declaration order
+    /// and the listener must be unregistered before the closure is freed.
+    disposable: DisposableWrapper<Disposable>,
+    /// The closure that was handed to xterm.js.
+    closure: Closure<F>,
+}
+
+impl<F: ?Sized + WasmClosure> Debug for EventListener<F> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("EventListener")
+            .field("disposable", &self.disposable)
+            .field("closure", &self.closure)
+            .finish()
+    }
+}
+
+impl<F: ?Sized + WasmClosure> EventListener<F> {
+    /// Registers `closure` using `register` (i.e. `Terminal::on_data`).
+    pub fn new<R>(closure: Closure<F>, register: R) -> Self
+    where
+        R: FnOnce(&Closure<F>) -> Disposable,
+    {
+        Self {
+            disposable: register(&closure).into(),
+            closure,
+        }
+    }
+}
+
+/// Events that have been received but not yet yielded by an [`EventStream`].
+#[derive(Debug)]
+struct EventQueue<T> {
+    /// The events, oldest first.
+    events: VecDeque<T>,
+    /// The task waiting on the next event.
+    waker: Option<Waker>,
+}
+
+impl<T> EventQueue<T> {
+    /// Adds an event to the queue, waking the waiting task (if any).
+    fn push(queue: &RefCell<Self>, event: T) {
+        let waker = {
+            let mut queue = queue.borrow_mut();
+            queue.events.push_back(event);
+            queue.waker.take()
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// A [`Stream`] of events.
+///
+/// Events are buffered until they are polled for; the stream never ends.
+/// Dropping the stream disposes of the underlying JS listener and frees its
+/// closure.
+pub struct EventStream<T> {
+    /// Events that have arrived but haven't been yielded yet.
+    queue: Rc<RefCell<EventQueue<T>>>,
+    /// The [`EventListener`] feeding the queue (type erased since the closure
+    /// type depends on the event).
+    listener: Box<dyn Any>,
+}
+
+impl<T: Debug> Debug for EventStream<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("EventStream")
+            .field("queue", &self.queue)
+            .field("listener", &self.listener)
+            .finish()
+    }
+}
+
+impl<T: 'static> EventStream<T> {
+    /// Makes a stream fed by the listener(s) that `listen` creates; `listen`
+    /// is given a function that queues up an event.
+    ///
+    /// The listener(s) are kept alive for as long as the stream is.
+    pub(super) fn new<L: Any>(listen: impl FnOnce(Rc<dyn Fn(T)>) -> L) -> Self {
+        let queue = Rc::new(RefCell::new(EventQueue {
+            events: VecDeque::new(),
+            waker: None,
+        }));
+
+        let push: Rc<dyn Fn(T)> = {
+            let queue = queue.clone();
+            Rc::new(move |event| EventQueue::push(&queue, event))
+        };
+
+        Self {
+            queue,
+            listener: Box::new(listen(push)),
+        }
+    }
+}
+
+impl<T> Stream for EventStream<T> {
+    type Item = T;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<T>> {
+        let mut queue = self.queue.borrow_mut();
+
+        if let Some(event) = queue.events.pop_front() {
+            Poll::Ready(Some(event))
+        } else {
+            queue.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+#[rustfmt::skip]
+impl Terminal { event_streams! {
+    @doc: "binary"
+    pub binary_listener, binary_stream: (Str) => Terminal::on_binary
+
+    @doc: "cursor move"
+    pub cursor_move_listener, cursor_move_stream: () => Terminal::on_cursor_move
+
+    @doc: "data"
+    pub data_listener, data_stream: (Str) => Terminal::on_data
+
+    @doc: "key event"
+    pub key_listener, key_stream: (KeyEventData) => Terminal::on_key
+
+    @doc: "line feed"
+    pub line_feed_listener, line_feed_stream: () => Terminal::on_line_feed
+
+    @doc: "render"
+    pub render_listener, render_stream: (RenderEventData)
+        => Terminal::on_render
+
+    @doc: "resize"
+    pub resize_listener, resize_stream: (ResizeEventData)
+        => Terminal::on_resize
+
+    @doc: "scroll"
+    pub scroll_listener, scroll_stream: (u32) => Terminal::on_scroll
+
+    @doc: "selection change"
+    pub selection_change_listener, selection_change_stream: ()
+        => Terminal::on_selection_change
+
+    @doc: "title change"
+    pub title_change_listener, title_change_stream: (Str)
+        => Terminal::on_title_change
+}}
+
+#[rustfmt::skip]
+impl BufferNamespace { event_streams! {
+    @doc: "buffer change"
+    pub buffer_change_listener, buffer_change_stream: (Buffer)
+        => BufferNamespace::on_buffer_change
+}}
+
+#[rustfmt::skip]
+impl Marker { event_streams! {
+    @doc: "dispose"
+    pub dispose_listener, dispose_stream: () => Marker::on_dispose
+}}
@@ -0,0 +1,413 @@
+//! A Rust version of the [fit addon], which resizes a [`Terminal`] to fill its
+//! parent element.
+//!
+//! [`FitAddon`] is an [`XtermAddon`] so it's loaded like any other addon (i.e.
+//! with [`Terminal::load_xterm_addon`]). The sizing itself is done by
+//! [`propose_dimensions`], which works on [`FitMeasurements`] rather than on
+//! the DOM.
+//!
+//! [`Terminal::auto_fit`] keeps a terminal fitted to its parent element as the
+//! element changes size.
+//!
+//! [fit addon]: https://www.npmjs.com/package/xterm-addon-fit
+
+use super::{Terminal, XtermAddon, XtermDisposable};
+
+use js_sys::{Function, Reflect};
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::{Element, Window};
+
+use core::cell::{Cell, RefCell};
+use core::convert::TryFrom;
+use core::fmt::{self, Debug};
+use core::time::Duration;
+use std::rc::Rc;
+
+/// The fewest columns [`propose_dimensions`] will propose.
+pub const MINIMUM_COLS: u16 = 2;
+
+/// The fewest rows [`propose_dimensions`] will propose.
+pub const MINIMUM_ROWS: u16 = 1;
+
+/// Padding around an element, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Padding {
+    /// Padding above the element.
+    pub top: f64,
+    /// Padding to the right of the element.
+    pub right: f64,
+    /// Padding below the element.
+    pub bottom: f64,
+    /// Padding to the left of the element.
+    pub left: f64,
+}
+
+/// Everything (in pixels) that goes into sizing a terminal to fit its parent
+/// element.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FitMeasurements {
+    /// The width of the terminal's parent element.
+    pub parent_width: f64,
+    /// The height of the terminal's parent element.
+    pub parent_height: f64,
+    /// The padding of the terminal's element.
+    pub padding: Padding,
+    /// The width of the terminal's scrollbar.
+    pub scrollbar_width: f64,
+    /// The width of a cell.
+    pub cell_width: f64,
+    /// The height of a cell.
+    pub cell_height: f64,
+}
+
+/// The number of columns and rows (in that order) that fit in the space
+/// described by `measurements`.
+///
+/// This matches the fit addon's `proposeDimensions`: the terminal's padding
+/// and scrollbar are taken out of the parent element's size and the rest is
+/// divided into whole cells, with at least [`MINIMUM_COLS`] columns and
+/// [`MINIMUM_ROWS`] rows.
+///
+/// Returns `None` if the cell size isn't known yet (i.e. is zero).
+#[must_use]
+pub fn propose_dimensions(
+    measurements: &FitMeasurements,
+) -> Option<(u16, u16)> {
+    let FitMeasurements {
+        parent_width,
+        parent_height,
+        padding,
+        scrollbar_width,
+        cell_width,
+        cell_height,
+    } = *measurements;
+
+    if !(cell_width > 0.0 && cell_height > 0.0) {
+        return None;
+    }
+
+    let available_width =
+        parent_width.max(0.0) - padding.left - padding.right - scrollbar_width;
+    let available_height = parent_height - padding.top - padding.bottom;
+
+    // Negative (and NaN) sizes become 0. This is done by hand because
+    // float to int casts only saturate as of Rust 1.45.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let fit = |available: f64, cell: f64, minimum: u16| {
+        let cells = (available / cell).floor();
+        let cells = if cells.is_nan() || cells <= 0.0 {
+            0
+        } else if cells >= f64::from(u16::MAX) {
+            u16::MAX
+        } else {
+            cells as u16
+        };
+
+        cells.max(minimum)
+    };
+
+    Some((
+        fit(available_width, cell_width, MINIMUM_COLS),
+        fit(available_height, cell_height, MINIMUM_ROWS),
+    ))
+}
+
+/// Reads a number out of a chain of (possibly private) properties, starting
+/// at `obj`.
+fn read_number(obj: &JsValue, path: &[&str]) -> Option<f64> {
+    path.iter()
+        .try_fold(obj.clone(), |obj, key| {
+            Reflect::get(&obj, &JsValue::from_str(key)).ok()
+        })?
+        .as_f64()
+}
+
+/// Reads a pixel value (i.e. `"12.5px"`) out of a computed style.
+fn read_pixels(element: &Element, property: &str) -> Option<f64> {
+    let style = web_sys::window()?.get_computed_style(element).ok()??;
+    let value = style.get_property_value(property).ok()?;
+
+    value.trim_end_matches("px").trim().parse().ok()
+}
+
+impl FitMeasurements {
+    /// Measures `terminal`; returns `None` if the terminal hasn't been opened
+    /// (or isn't in the DOM).
+    ///
+    /// Like the fit addon, this reads the cell size and scrollbar width from
+    /// xterm.js's internals since they aren't exposed otherwise.
+    #[must_use]
+    pub fn measure(terminal: &Terminal) -> Option<Self> {
+        let element = terminal.element()?;
+        let parent = element.parent_element()?;
+        let core = Reflect::get(terminal, &JsValue::from_str("_core")).ok()?;
+
+        let padding = |side: &str| {
+            read_pixels(&element, &format!("padding-{}", side)).unwrap_or(0.0)
+        };
+
+        Some(Self {
+            parent_width: read_pixels(&parent, "width")?,
+            parent_height: read_pixels(&parent, "height")?,
+            padding: Padding {
+                top: padding("top"),
+                right: padding("right"),
+                bottom: padding("bottom"),
+                left: padding("left"),
+            },
+            scrollbar_width: read_number(
+                &core,
+                &["viewport", "scrollBarWidth"],
+            )
+            .unwrap_or(0.0),
+            cell_width: read_number(
+                &core,
+                &["_renderService", "dimensions", "actualCellWidth"],
+            )?,
+            cell_height: read_number(
+                &core,
+                &["_renderService", "dimensions", "actualCellHeight"],
+            )?,
+        })
+    }
+}
+
+/// Resizes a [`Terminal`] to fill its parent element.
+///
+/// Clones share the terminal the addon was activated with, so a clone can be
+/// kept around to call [`FitAddon::fit`] after the addon is loaded.
+#[derive(Debug, Clone, Default)]
+pub struct FitAddon {
+    /// The terminal the addon was activated with (if it's active).
+    terminal: Rc<RefCell<Option<Terminal>>>,
+}
+
+impl FitAddon {
+    /// Creates a fit addon; it does nothing until it's loaded into a terminal.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of columns and rows (in that order) that would fill the
+    /// terminal's parent element.
+    ///
+    /// Returns `None` if the addon isn't active or the terminal can't be
+    /// measured yet (see [`FitMeasurements::measure`]).
+    #[must_use]
+    pub fn propose_dimensions(&self) -> Option<(u16, u16)> {
+        let terminal = self.terminal.borrow();
+        propose_dimensions(&FitMeasurements::measure(terminal.as_ref()?)?)
+    }
+
+    /// Resizes the terminal to fill its parent element.
+    ///
+    /// Does nothing if the size is already right or if the dimensions can't be
+    /// [proposed](FitAddon::propose_dimensions).
+    pub fn fit(&self) {
+        if let Some(terminal) = self.terminal.borrow().as_ref() {
+            let _ = fit_terminal(terminal);
+        }
+    }
+}
+
+/// Resizes `terminal` to fill its parent element; returns the new size (cols,
+/// rows) if the terminal was resized.
+fn fit_terminal(terminal: &Terminal) -> Option<(u16, u16)> {
+    let (cols, rows) =
+        propose_dimensions(&FitMeasurements::measure(terminal)?)?;
+    if terminal.cols() == cols && terminal.rows() == rows {
+        return None;
+    }
+
+    // The fit addon clears the renderer first; we do the same (if we can).
+    let _ = Reflect::get(terminal, &JsValue::from_str("_core"))
+        .and_then(|core| {
+            Reflect::get(&core, &JsValue::from_str("_renderService"))
+        })
+        .and_then(|service| {
+            let clear = Reflect::get(&service, &JsValue::from_str("clear"))?;
+            clear.dyn_into::<Function>()?.call0(&service)
+        });
+
+    terminal.resize(cols, rows);
+    Some((cols, rows))
+}
+
+impl XtermDisposable for FitAddon {
+    fn dispose(&self) {
+        let _ = self.terminal.borrow_mut().take();
+    }
+}
+
+impl XtermAddon for FitAddon {
+    fn activate(&self, terminal: Terminal) {
+        *self.terminal.borrow_mut() = Some(terminal);
+    }
+}
+
+#[wasm_bindgen]
+extern "C" {
+    /// The DOM's [`ResizeObserver`](https://developer.mozilla.org/en-US/docs/Web/API/ResizeObserver).
+    #[derive(Debug, Clone)]
+    type ResizeObserver;
+
+    /// Throws if the browser doesn't have `ResizeObserver`.
+    #[wasm_bindgen(catch, constructor)]
+    fn new(callback: &Function) -> Result<ResizeObserver, JsValue>;
+
+    /// Starts observing `target`.
+    #[wasm_bindgen(method)]
+    fn observe(this: &ResizeObserver, target: &Element);
+
+    /// Stops observing everything.
+    #[wasm_bindgen(method)]
+    fn disconnect(this: &ResizeObserver);
+}
+
+/// What an [`AutoFit`] uses to find out about size changes.
+#[derive(Debug)]
+enum Watcher {
+    /// Observes the terminal's parent element.
+    Observer(ResizeObserver),
+    /// Listens for `resize` events on the window; used when there's no
+    /// `ResizeObserver` (or nothing to observe).
+    Window,
+}
+
+/// Keeps a [`Terminal`] fitted to its parent element.
+///
+/// Made with [`Terminal::auto_fit`]. Dropping this stops watching for size
+/// changes.
+pub struct AutoFit {
+    /// The window; used for timers (and `resize` events).
+    window: Window,
+    /// What's watching for size changes.
+    watcher: Watcher,
+    /// Called on size changes; (re)starts the debounce timer.
+    on_change: Closure<dyn FnMut()>,
+    /// Run by the debounce timer; fits the terminal.
+    fit: Closure<dyn FnMut()>,
+    /// The debounce timer, if it's running.
+    pending: Rc<Cell<Option<i32>>>,
+}
+
+impl Debug for AutoFit {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("AutoFit")
+            .field("window", &self.window)
+            .field("watcher", &self.watcher)
+            .field("on_change", &self.on_change)
+            .field("fit", &self.fit)
+            .field("pending", &self.pending)
+            .finish()
+    }
+}
+
+impl Drop for AutoFit {
+    fn drop(&mut self) {
+        match self.watcher {
+            Watcher::Observer(ref observer) => observer.disconnect(),
+            Watcher::Window => {
+                let _ = self.window.remove_event_listener_with_callback(
+                    "resize",
+                    self.on_change.as_ref().unchecked_ref(),
+                );
+            }
+        }
+
+        if let Some(handle) = self.pending.take() {
+            self.window.clear_timeout_with_handle(handle);
+        }
+    }
+}
+
+impl Terminal {
+    /// Keeps this terminal fitted to its parent element (see [`FitAddon`]),
+    /// until the returned [`AutoFit`] is dropped.
+    ///
+    /// The parent element is watched with a `ResizeObserver`; if there isn't
+    /// one (or the terminal hasn't been opened yet) the window's `resize`
+    /// events are used instead. Size changes are debounced: the terminal is
+    /// fitted once there haven't been any for `debounce`.
+    ///
+    /// The terminal is only resized when its columns or rows actually change;
+    /// when it is, `on_resize` is called with the new size (cols, rows) (i.e.
+    /// to tell a PTY about it).
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's no `window` (xterm.js needs one anyway).
+    pub fn auto_fit<F>(&self, debounce: Duration, on_resize: F) -> AutoFit
+    where
+        F: FnMut(u16, u16) + 'static,
+    {
+        let window = web_sys::window().expect("xterm.js needs a `window`");
+        let pending = Rc::new(Cell::new(None));
+
+        let fit: Closure<dyn FnMut()> = {
+            let terminal = self.clone();
+            let pending = pending.clone();
+            let mut on_resize = on_resize;
+
+            let fit: Box<dyn FnMut()> = Box::new(move || {
+                pending.set(None);
+                if let Some((cols, rows)) = fit_terminal(&terminal) {
+                    on_resize(cols, rows);
+                }
+            });
+            Closure::wrap(fit)
+        };
+
+        let on_change: Closure<dyn FnMut()> = {
+            let window = window.clone();
+            let pending = pending.clone();
+            let fit: Function =
+                fit.as_ref().unchecked_ref::<Function>().clone();
+            let delay = i32::try_from(debounce.as_millis()).unwrap_or(i32::MAX);
+
+            let on_change: Box<dyn FnMut()> = Box::new(move || {
+                if let Some(handle) = pending.take() {
+                    window.clear_timeout_with_handle(handle);
+                }
+
+                let handle = window
+                    .set_timeout_with_callback_and_timeout_and_arguments_0(
+                        &fit, delay,
+                    );
+                pending.set(handle.ok());
+            });
+            Closure::wrap(on_change)
+        };
+
+        let observer = self
+            .element()
+            .and_then(|element| element.parent_element())
+            .and_then(|parent| {
+                let observer =
+                    ResizeObserver::new(on_change.as_ref().unchecked_ref())
+                        .ok()?;
+                observer.observe(&parent);
+
+                Some(observer)
+            });
+
+        let watcher = if let Some(observer) = observer {
+            Watcher::Observer(observer)
+        } else {
+            let _ = window.add_event_listener_with_callback(
+                "resize",
+                on_change.as_ref().unchecked_ref(),
+            );
+            Watcher::Window
+        };
+
+        AutoFit {
+            window,
+            watcher,
+            on_change,
+            fit,
+            pending,
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! Flow control for writes.
+//!
+//! xterm.js buffers writes it can't process right away; a producer that's
+//! faster than the terminal (i.e. a PTY running `cat` on a large file) will
+//! freeze the tab and make memory usage balloon. The [xterm.js docs] recommend
+//! keeping track of how much data has been written but not yet processed and
+//! pausing the producer when that goes above a _high watermark_, resuming it
+//! once it drops below a _low watermark_.
+//!
+//! [`FlowControlledWriter`] does exactly that.
+//!
+//! [xterm.js docs]: https://xtermjs.org/docs/guides/flowcontrol/
+
+use crate::xterm::Terminal;
+
+use futures_sink::Sink;
+use js_sys::Uint8Array;
+use wasm_bindgen::{prelude::*, JsCast};
+
+use core::cell::{Cell, RefCell};
+use core::convert::Infallible;
+use core::fmt::{self, Debug};
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use std::rc::Rc;
+
+/// State shared between a [`FlowControlledWriter`] and its write callbacks.
+#[derive(Default)]
+struct FlowState {
+    /// Bytes written but not yet processed by xterm.js.
+    pending: Cell<usize>,
+    /// Whether the producer has been told to pause.
+    paused: Cell<bool>,
+    /// The task waiting for the writer to be ready or flushed.
+    waker: RefCell<Option<Waker>>,
+    /// Called when the pending byte count goes above the high watermark.
+    on_pause: RefCell<Option<Box<dyn FnMut()>>>,
+    /// Called when the pending byte count drops to the low watermark.
+    on_resume: RefCell<Option<Box<dyn FnMut()>>>,
+}
+
+impl FlowState {
+    /// Wakes the waiting task, if there is one.
+    fn wake(&self) {
+        let waker = self.waker.borrow_mut().take();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Calls a pause/resume handler, if one is set.
+    fn signal(handler: &RefCell<Option<Box<dyn FnMut()>>>) {
+        if let Some(ref mut handler) = *handler.borrow_mut() {
+            handler();
+        }
+    }
+}
+
+/// A writer that keeps track of how much data xterm.js has yet to process and
+/// signals the producer to pause and resume at configurable watermarks.
+///
+/// Writes are never refused; it's up to the producer to honor the pause
+/// signal, either by using the [`on_pause`]/[`on_resume`] handlers (i.e. to
+/// send XOFF/XON or stop reading from a socket) or by using the writer as a
+/// [`Sink`], which won't be ready for more data while the writer is paused.
+///
+/// [`on_pause`]: FlowControlledWriter::on_pause
+/// [`on_resume`]: FlowControlledWriter::on_resume
+pub struct FlowControlledWriter {
+    /// The terminal we're writing to.
+    terminal: Terminal,
+    /// Pause once more than this many bytes are pending.
+    high_watermark: usize,
+    /// Resume once this many (or fewer) bytes are pending.
+    low_watermark: usize,
+    /// Shared with the write callbacks.
+    state: Rc<FlowState>,
+}
+
+impl Debug for FlowControlledWriter {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("FlowControlledWriter")
+            .field("terminal", &self.terminal)
+            .field("high_watermark", &self.high_watermark)
+            .field("low_watermark", &self.low_watermark)
+            .field("pending", &self.state.pending.get())
+            .field("paused", &self.state.paused.get())
+            .finish()
+    }
+}
+
+impl FlowControlledWriter {
+    /// The high watermark [`FlowControlledWriter::new`] uses.
+    pub const DEFAULT_HIGH_WATERMARK: usize = 100_000;
+    /// The low watermark [`FlowControlledWriter::new`] uses.
+    pub const DEFAULT_LOW_WATERMARK: usize = 10_000;
+
+    /// Creates a writer with the [default] watermarks.
+    ///
+    /// [default]: FlowControlledWriter::DEFAULT_HIGH_WATERMARK
+    #[must_use]
+    pub fn new(terminal: Terminal) -> Self {
+        Self::with_watermarks(
+            terminal,
+            Self::DEFAULT_HIGH_WATERMARK,
+            Self::DEFAULT_LOW_WATERMARK,
+        )
+    }
+
+    /// Creates a writer that pauses once more than `high_watermark` bytes are
+    /// pending and resumes once no more than `low_watermark` bytes are
+    /// pending.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `low_watermark` is greater than `high_watermark`.
+    #[must_use]
+    pub fn with_watermarks(
+        terminal: Terminal,
+        high_watermark: usize,
+        low_watermark: usize,
+    ) -> Self {
+        assert!(
+            low_watermark <= high_watermark,
+            "the low watermark must not be above the high watermark"
+        );
+
+        Self {
+            terminal,
+            high_watermark,
+            low_watermark,
+            state: Rc::new(FlowState::default()),
+        }
+    }
+
+    /// Sets the function that's called when the producer should pause.
+    pub fn on_pause<F: FnMut() + 'static>(&mut self, handler: F) -> &mut Self {
+        *self.state.on_pause.borrow_mut() = Some(Box::new(handler));
+        self
+    }
+
+    /// Sets the function that's called when the producer can resume.
+    pub fn on_resume<F: FnMut() + 'static>(&mut self, handler: F) -> &mut Self {
+        *self.state.on_resume.borrow_mut() = Some(Box::new(handler));
+        self
+    }
+
+    /// The number of bytes that have been written but not yet processed.
+    #[must_use]
+    pub fn pending(&self) -> usize {
+        self.state.pending.get()
+    }
+
+    /// Whether the producer should currently be paused.
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        self.state.paused.get()
+    }
+
+    /// The terminal this writer writes to.
+    #[must_use]
+    pub fn terminal(&self) -> &Terminal {
+        &self.terminal
+    }
+
+    /// Writes a string to the terminal.
+    pub fn write_str(&self, data: &str) {
+        self.write_js(&JsValue::from_str(data), data.len());
+    }
+
+    /// Writes raw (UTF-8 encoded) bytes to the terminal.
+    pub fn write_bytes(&self, data: &[u8]) {
+        self.write_js(&Uint8Array::from(data), data.len());
+    }
+
+    /// Writes `data` (a string or a `Uint8Array`), counting it as `len` bytes.
+    fn write_js(&self, data: &JsValue, len: usize) {
+        let state = &self.state;
+
+        state.pending.set(state.pending.get() + len);
+        if !state.paused.get() && state.pending.get() > self.high_watermark {
+            state.paused.set(true);
+            FlowState::signal(&state.on_pause);
+        }
+
+        let callback = Closure::once_into_js({
+            let state = self.state.clone();
+            let low_watermark = self.low_watermark;
+
+            move || {
+                let pending = state.pending.get().saturating_sub(len);
+                state.pending.set(pending);
+
+                if state.paused.get() && pending <= low_watermark {
+                    state.paused.set(false);
+                    FlowState::signal(&state.on_resume);
+                    state.wake();
+                } else if pending == 0 {
+                    state.wake();
+                }
+            }
+        });
+
+        self.terminal
+            .write_with_function(data, callback.unchecked_ref());
+    }
+
+    /// Returns `Ready` if `ready` is true; otherwise registers the task to be
+    /// woken.
+    fn poll_until(&self, cx: &mut Context<'_>, ready: bool) -> Poll<()> {
+        if ready {
+            Poll::Ready(())
+        } else {
+            *self.state.waker.borrow_mut() = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+/// Accepts anything that can be viewed as bytes (`&str`, `String`, `&[u8]`,
+/// `Vec<u8>`, etc.).
+///
+/// The sink isn't ready while the writer is paused; flushing (and closing)
+/// waits until xterm.js has processed everything that's been written.
+impl<T: AsRef<[u8]>> Sink<T> for FlowControlledWriter {
+    type Error = Infallible;
+
+    fn poll_ready(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Infallible>> {
+        self.poll_until(cx, !self.is_paused()).map(Ok)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Infallible> {
+        self.write_bytes(item.as_ref());
+        Ok(())
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Infallible>> {
+        self.poll_until(cx, self.pending() == 0).map(Ok)
+    }
+
+    fn poll_close(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Infallible>> {
+        <Self as Sink<T>>::poll_flush(self, cx)
+    }
+}
@@ -0,0 +1,559 @@
+//! A decoder for the input the terminal sends (i.e. via [`Terminal::on_data`]).
+//!
+//! xterm.js hands us input as the raw text a terminal would send to a program:
+//! printable characters, control characters and escape sequences. This module
+//! turns that text into [`InputEvent`]s. It supports:
+//!   - keys (including CSI and SS3 encoded keys with modifiers)
+//!   - mouse reports in the X10, UTF-8 (1005), SGR (1006) and URXVT (1015)
+//!     encodings
+//!   - focus in/out reports
+//!   - bracketed pastes
+//!
+//! The decoder is pure Rust (it doesn't touch any JS) so it can be used and
+//! tested natively.
+//!
+//! [`Terminal::on_data`]: crate::xterm::Terminal::on_data
+
+use super::EventListener;
+use crate::xterm::{Str, Terminal};
+
+use core::iter::Peekable;
+use core::str::Chars;
+
+/// Modifier keys that were held down.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Modifiers {
+    /// Shift.
+    pub shift: bool,
+    /// Alt (or Option).
+    pub alt: bool,
+    /// Control.
+    pub ctrl: bool,
+    /// Meta (or Command, or the Windows key).
+    pub meta: bool,
+}
+
+impl Modifiers {
+    /// No modifiers.
+    pub const NONE: Self = Self {
+        shift: false,
+        alt: false,
+        ctrl: false,
+        meta: false,
+    };
+
+    /// Decodes the modifier parameter used in CSI key sequences (i.e. the `5`
+    /// in `CSI 1 ; 5 A`): one plus a bitmask of the modifiers.
+    #[must_use]
+    pub fn from_param(param: u16) -> Self {
+        let bits = param.saturating_sub(1);
+
+        Self {
+            shift: bits & 1 != 0,
+            alt: bits & 2 != 0,
+            ctrl: bits & 4 != 0,
+            meta: bits & 8 != 0,
+        }
+    }
+
+    /// Encodes these modifiers as a CSI key sequence parameter; the inverse
+    /// of [`Modifiers::from_param`].
+    #[must_use]
+    pub fn to_param(self) -> u16 {
+        1 + u16::from(self.shift)
+            + (u16::from(self.alt) << 1)
+            + (u16::from(self.ctrl) << 2)
+            + (u16::from(self.meta) << 3)
+    }
+
+    /// Whether any modifiers are held down.
+    #[must_use]
+    pub fn any(self) -> bool {
+        self != Self::NONE
+    }
+}
+
+/// A key.
+#[allow(variant_size_differences)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyCode {
+    /// A character.
+    Char(char),
+    /// Enter (or Return).
+    Enter,
+    /// Tab.
+    Tab,
+    /// Shift + Tab.
+    BackTab,
+    /// Backspace.
+    Backspace,
+    /// Escape.
+    Escape,
+    /// The up arrow.
+    Up,
+    /// The down arrow.
+    Down,
+    /// The left arrow.
+    Left,
+    /// The right arrow.
+    Right,
+    /// Home.
+    Home,
+    /// End.
+    End,
+    /// Page Up.
+    PageUp,
+    /// Page Down.
+    PageDown,
+    /// Insert.
+    Insert,
+    /// Delete.
+    Delete,
+    /// A function key (i.e. `F(1)` is F1).
+    F(u8),
+}
+
+/// A key press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyInput {
+    /// The key.
+    pub code: KeyCode,
+    /// The modifiers that were held down.
+    pub modifiers: Modifiers,
+}
+
+impl KeyInput {
+    /// A key press with the given modifiers.
+    #[must_use]
+    pub fn new(code: KeyCode, modifiers: Modifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    /// A key press without modifiers.
+    #[must_use]
+    pub fn plain(code: KeyCode) -> Self {
+        Self::new(code, Modifiers::NONE)
+    }
+}
+
+/// A mouse button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    /// The left (primary) button.
+    Left,
+    /// The middle button.
+    Middle,
+    /// The right (secondary) button.
+    Right,
+}
+
+/// What happened with the mouse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseAction {
+    /// A button was pressed.
+    Down(MouseButton),
+    /// A button was released. Some encodings (X10, URXVT) don't say which
+    /// button was released.
+    Up(Option<MouseButton>),
+    /// The mouse moved while a button was held down.
+    Drag(MouseButton),
+    /// The mouse moved without a button being held down.
+    Moved,
+    /// The wheel was scrolled up.
+    ScrollUp,
+    /// The wheel was scrolled down.
+    ScrollDown,
+    /// The wheel was scrolled left.
+    ScrollLeft,
+    /// The wheel was scrolled right.
+    ScrollRight,
+}
+
+/// A mouse report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MouseInput {
+    /// What happened.
+    pub action: MouseAction,
+    /// The (0-based) column of the mouse.
+    pub column: u16,
+    /// The (0-based) row of the mouse.
+    pub row: u16,
+    /// The modifiers that were held down. Mouse reports can't carry `meta`.
+    pub modifiers: Modifiers,
+}
+
+impl MouseInput {
+    /// Decodes a mouse report from its button byte (without the offset of 32
+    /// that the X10 and URXVT encodings add) and its 1-based coordinates.
+    ///
+    /// `released` is only used by the SGR encoding.
+    fn decode(button: u16, column: u16, row: u16, released: bool) -> Self {
+        let modifiers = Modifiers {
+            shift: button & 4 != 0,
+            alt: button & 8 != 0,
+            ctrl: button & 16 != 0,
+            meta: false,
+        };
+
+        let which = match button & 3 {
+            0 => Some(MouseButton::Left),
+            1 => Some(MouseButton::Middle),
+            2 => Some(MouseButton::Right),
+            _ => None,
+        };
+
+        let action = if button & 64 != 0 {
+            match button & 3 {
+                0 => MouseAction::ScrollUp,
+                1 => MouseAction::ScrollDown,
+                2 => MouseAction::ScrollLeft,
+                _ => MouseAction::ScrollRight,
+            }
+        } else if button & 32 != 0 {
+            which.map_or(MouseAction::Moved, MouseAction::Drag)
+        } else if released {
+            MouseAction::Up(which)
+        } else {
+            which.map_or(MouseAction::Up(None), MouseAction::Down)
+        };
+
+        Self {
+            action,
+            column: column.saturating_sub(1),
+            row: row.saturating_sub(1),
+            modifiers,
+        }
+    }
+}
+
+/// A decoded piece of input.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum InputEvent {
+    /// A key press.
+    Key(KeyInput),
+    /// A mouse report.
+    Mouse(MouseInput),
+    /// The terminal gained focus (with focus reporting, mode 1004, on).
+    FocusGained,
+    /// The terminal lost focus (with focus reporting, mode 1004, on).
+    FocusLost,
+    /// Text that was pasted (with bracketed paste, mode 2004, on).
+    Paste(String),
+    /// An escape sequence that the decoder doesn't understand.
+    Unknown(String),
+}
+
+/// Starts a bracketed paste.
+const PASTE_START: &str = "\x1b[200~";
+/// Ends a bracketed paste.
+const PASTE_END: &str = "\x1b[201~";
+
+/// Decodes input from the terminal into [`InputEvent`]s.
+///
+/// xterm.js sends each key press (and each paste) in its own chunk so the
+/// decoder treats each chunk as complete; the only state it carries between
+/// chunks is an unterminated bracketed paste.
+#[derive(Debug, Clone, Default)]
+pub struct InputDecoder {
+    /// The text of a bracketed paste that hasn't ended yet.
+    paste: Option<String>,
+}
+
+impl InputDecoder {
+    /// Creates a new decoder.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the decoder is in the middle of a bracketed paste.
+    #[must_use]
+    pub fn in_paste(&self) -> bool {
+        self.paste.is_some()
+    }
+
+    /// Decodes a chunk of input.
+    pub fn feed(&mut self, data: &str) -> Vec<InputEvent> {
+        let mut events = Vec::new();
+        let mut rest = data;
+
+        while !rest.is_empty() {
+            if let Some(mut paste) = self.paste.take() {
+                if let Some(end) = rest.find(PASTE_END) {
+                    paste.push_str(&rest[..end]);
+                    rest = &rest[(end + PASTE_END.len())..];
+                    events.push(InputEvent::Paste(paste));
+                } else {
+                    paste.push_str(rest);
+                    rest = "";
+                    self.paste = Some(paste);
+                }
+
+                continue;
+            }
+
+            let (chunk, next) = match rest.find(PASTE_START) {
+                Some(start) => (&rest[..start], Some(start)),
+                None => (rest, None),
+            };
+
+            decode_into(chunk, &mut events);
+
+            if let Some(start) = next {
+                self.paste = Some(String::new());
+                rest = &rest[(start + PASTE_START.len())..];
+            } else {
+                rest = "";
+            }
+        }
+
+        events
+    }
+}
+
+impl Terminal {
+    /// Attaches a data event listener that decodes the data into
+    /// [`InputEvent`]s and calls `listener` with each one.
+    ///
+    /// See [`Terminal::data_listener`].
+    pub fn input_listener<F>(
+        &self,
+        mut listener: F,
+    ) -> EventListener<dyn FnMut(Str)>
+    where
+        F: FnMut(InputEvent) + 'static,
+    {
+        let mut decoder = InputDecoder::new();
+        self.data_listener(move |data| {
+            decoder.feed(&data).into_iter().for_each(&mut listener);
+        })
+    }
+}
+
+/// Decodes a single chunk of input.
+///
+/// Bracketed pastes that start and end within `data` are decoded; use an
+/// [`InputDecoder`] if pastes may be split across chunks.
+#[must_use]
+pub fn decode_input(data: &str) -> Vec<InputEvent> {
+    let mut decoder = InputDecoder::new();
+    let mut events = decoder.feed(data);
+
+    if let Some(paste) = decoder.paste {
+        events.push(InputEvent::Paste(paste));
+    }
+
+    events
+}
+
+/// Decodes text that doesn't contain bracketed paste markers.
+fn decode_into(data: &str, events: &mut Vec<InputEvent>) {
+    let mut chars = data.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let event = if c == '\x1b' {
+            decode_escape(&mut chars)
+        } else {
+            InputEvent::Key(decode_char(c))
+        };
+
+        events.push(event);
+    }
+}
+
+/// Decodes a character that isn't part of an escape sequence.
+fn decode_char(c: char) -> KeyInput {
+    let ctrl = Modifiers {
+        ctrl: true,
+        ..Modifiers::NONE
+    };
+
+    match c {
+        '\r' | '\n' => KeyInput::plain(KeyCode::Enter),
+        '\t' => KeyInput::plain(KeyCode::Tab),
+        '\x7f' => KeyInput::plain(KeyCode::Backspace),
+        '\x1b' => KeyInput::plain(KeyCode::Escape),
+        '\0' => KeyInput::new(KeyCode::Char(' '), ctrl),
+        '\x01'..='\x1a' => {
+            let letter = (b'a' + (c as u8) - 1) as char;
+            KeyInput::new(KeyCode::Char(letter), ctrl)
+        }
+        '\x1c'..='\x1f' => {
+            let digit = (b'4' + (c as u8) - 0x1c) as char;
+            KeyInput::new(KeyCode::Char(digit), ctrl)
+        }
+        c => KeyInput::plain(KeyCode::Char(c)),
+    }
+}
+
+/// Decodes what follows an escape character.
+fn decode_escape(chars: &mut Peekable<Chars<'_>>) -> InputEvent {
+    match chars.next() {
+        None => InputEvent::Key(KeyInput::plain(KeyCode::Escape)),
+        Some('[') => decode_csi(chars),
+        Some('O') => match chars.next() {
+            Some(c) => match key_for_final(c) {
+                Some(code) => InputEvent::Key(KeyInput::plain(code)),
+                None => InputEvent::Unknown(format!("\x1bO{}", c)),
+            },
+            // A lone `ESC O` is Alt + O.
+            None => InputEvent::Key(alt(decode_char('O'))),
+        },
+        Some(c) => InputEvent::Key(alt(decode_char(c))),
+    }
+}
+
+/// Adds alt to a key's modifiers.
+fn alt(mut key: KeyInput) -> KeyInput {
+    key.modifiers.alt = true;
+    key
+}
+
+/// The keys that are encoded with `CSI 1 ; <mod> <final>` or `SS3 <final>`.
+fn key_for_final(c: char) -> Option<KeyCode> {
+    Some(match c {
+        'A' => KeyCode::Up,
+        'B' => KeyCode::Down,
+        'C' => KeyCode::Right,
+        'D' => KeyCode::Left,
+        'H' => KeyCode::Home,
+        'F' => KeyCode::End,
+        'P' => KeyCode::F(1),
+        'Q' => KeyCode::F(2),
+        'R' => KeyCode::F(3),
+        'S' => KeyCode::F(4),
+        _ => return None,
+    })
+}
+
+/// The keys that are encoded with `CSI <num> ; <mod> ~`.
+#[allow(clippy::cast_possible_truncation)]
+fn key_for_tilde(num: u16) -> Option<KeyCode> {
+    Some(match num {
+        1 | 7 => KeyCode::Home,
+        2 => KeyCode::Insert,
+        3 => KeyCode::Delete,
+        4 | 8 => KeyCode::End,
+        5 => KeyCode::PageUp,
+        6 => KeyCode::PageDown,
+        11..=15 => KeyCode::F((num - 10) as u8),
+        17..=21 => KeyCode::F((num - 11) as u8),
+        23..=26 => KeyCode::F((num - 12) as u8),
+        28 | 29 => KeyCode::F((num - 13) as u8),
+        31..=34 => KeyCode::F((num - 14) as u8),
+        _ => return None,
+    })
+}
+
+/// Parses `;` separated numeric parameters; empty parameters are `None`.
+fn parse_params(params: &str) -> Option<Vec<Option<u16>>> {
+    params
+        .split(';')
+        .map(|p| {
+            if p.is_empty() {
+                Ok(None)
+            } else {
+                p.parse().map(Some)
+            }
+        })
+        .collect::<Result<_, _>>()
+        .ok()
+}
+
+/// Decodes what follows `ESC [`.
+fn decode_csi(chars: &mut Peekable<Chars<'_>>) -> InputEvent {
+    // X10 and UTF-8 (1005) mouse reports: `CSI M <b> <x> <y>` where each of
+    // the three is a single character, offset by 32.
+    if chars.peek() == Some(&'M') {
+        let _ = chars.next();
+        let report: Vec<u32> = chars.by_ref().take(3).map(u32::from).collect();
+
+        return match *report.as_slice() {
+            [b, x, y] if b >= 32 && x > 32 && y > 32 => {
+                #[allow(clippy::cast_possible_truncation)]
+                let (b, x, y) =
+                    ((b - 32) as u16, (x - 32) as u16, (y - 32) as u16);
+                InputEvent::Mouse(MouseInput::decode(b, x, y, false))
+            }
+            _ => InputEvent::Unknown(format!(
+                "\x1b[M{}",
+                report
+                    .iter()
+                    .filter_map(|&c| core::char::from_u32(c))
+                    .collect::<String>()
+            )),
+        };
+    }
+
+    let mut seq = String::new();
+    let mut fin = None;
+    for c in chars.by_ref() {
+        if ('\x40'..='\x7e').contains(&c) {
+            fin = Some(c);
+            break;
+        }
+
+        seq.push(c);
+    }
+
+    let fin = match fin {
+        Some(fin) => fin,
+        None => return InputEvent::Unknown(format!("\x1b[{}", seq)),
+    };
+
+    decode_csi_sequence(&seq, fin)
+        .unwrap_or_else(|| InputEvent::Unknown(format!("\x1b[{}{}", seq, fin)))
+}
+
+/// Decodes a complete CSI sequence given its parameters and final character.
+fn decode_csi_sequence(seq: &str, fin: char) -> Option<InputEvent> {
+    // SGR (1006) mouse reports: `CSI < b ; x ; y M` (or `m` for releases).
+    // Note: `str::strip_prefix` is newer than our MSRV.
+    #[allow(clippy::manual_strip)]
+    if seq.starts_with('<') {
+        return match (parse_params(&seq[1..])?.as_slice(), fin) {
+            ([Some(b), Some(x), Some(y)], f) if f == 'M' || f == 'm' => Some(
+                InputEvent::Mouse(MouseInput::decode(*b, *x, *y, f == 'm')),
+            ),
+            _ => None,
+        };
+    }
+
+    let params = parse_params(seq)?;
+
+    match (params.as_slice(), fin) {
+        // URXVT (1015) mouse reports: `CSI b ; x ; y M`.
+        ([Some(b), Some(x), Some(y)], 'M') if *b >= 32 => {
+            Some(InputEvent::Mouse(MouseInput::decode(b - 32, *x, *y, false)))
+        }
+
+        ([None], 'I') => Some(InputEvent::FocusGained),
+        ([None], 'O') => Some(InputEvent::FocusLost),
+        ([None], 'Z') => Some(InputEvent::Key(KeyInput::new(
+            KeyCode::BackTab,
+            Modifiers {
+                shift: true,
+                ..Modifiers::NONE
+            },
+        ))),
+
+        // `CSI <final>` and `CSI 1 ; <mod> <final>`.
+        ([p], c) if c != '~' && (p.is_none() || *p == Some(1)) => {
+            key_for_final(c).map(|k| InputEvent::Key(KeyInput::plain(k)))
+        }
+        ([_, Some(m)], c) if c != '~' => key_for_final(c).map(|k| {
+            InputEvent::Key(KeyInput::new(k, Modifiers::from_param(*m)))
+        }),
+
+        // `CSI <num> ~` and `CSI <num> ; <mod> ~`.
+        ([Some(n)], '~') => {
+            key_for_tilde(*n).map(|k| InputEvent::Key(KeyInput::plain(k)))
+        }
+        ([Some(n), Some(m)], '~') => key_for_tilde(*n).map(|k| {
+            InputEvent::Key(KeyInput::new(k, Modifiers::from_param(*m)))
+        }),
+
+        _ => None,
+    }
+}
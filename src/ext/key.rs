@@ -0,0 +1,223 @@
+//! A richer Rust representation of [`KeyEventData`].
+//!
+//! [`KeyEventData`] only gives us the sequence xterm.js sent to the program
+//! and the raw DOM [`KeyboardEvent`]; [`Key`] pulls the interesting parts out
+//! of the latter. When the `crossterm-events` feature is enabled [`Key`]s
+//! (and the [`KeyInput`]s the [input decoder](super::input) produces) can be
+//! turned into crossterm [`KeyEvent`]s so that key handling code can be shared
+//! with native (tty) frontends.
+//!
+//! crossterm (as published) only builds for unix and windows targets, so the
+//! conversions aren't available on wasm targets even with the feature on.
+//!
+//! [`KeyboardEvent`]: web_sys::KeyboardEvent
+//! [`KeyEvent`]: https://docs.rs/crossterm/0.17.5/crossterm/event/struct.KeyEvent.html
+
+use super::{KeyCode, KeyInput, Modifiers};
+use crate::xterm::KeyEventData;
+
+/// Where on the keyboard a key is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyLocation {
+    /// The key isn't on a particular side of the keyboard or on the numpad.
+    Standard,
+    /// The left version of a key that appears more than once (i.e. left shift).
+    Left,
+    /// The right version of a key that appears more than once.
+    Right,
+    /// The key is on the numeric keypad.
+    Numpad,
+}
+
+impl KeyLocation {
+    /// Converts the value of `KeyboardEvent.location`.
+    #[must_use]
+    pub fn from_dom(location: u32) -> Self {
+        match location {
+            1 => Self::Left,
+            2 => Self::Right,
+            3 => Self::Numpad,
+            _ => Self::Standard,
+        }
+    }
+}
+
+impl Modifiers {
+    /// Gets the modifiers that were held down during a DOM [`KeyboardEvent`].
+    ///
+    /// [`KeyboardEvent`]: web_sys::KeyboardEvent
+    #[must_use]
+    pub fn from_dom(event: &web_sys::KeyboardEvent) -> Self {
+        Self {
+            shift: event.shift_key(),
+            alt: event.alt_key(),
+            ctrl: event.ctrl_key(),
+            meta: event.meta_key(),
+        }
+    }
+}
+
+impl KeyCode {
+    /// Converts a DOM `KeyboardEvent.key` value to a [`KeyCode`].
+    ///
+    /// `shift` turns `Tab` into [`KeyCode::BackTab`], like terminals do.
+    ///
+    /// Returns `None` for keys without a corresponding [`KeyCode`] (i.e.
+    /// modifier keys, or `"Unidentified"`).
+    #[must_use]
+    pub fn from_dom_key(key: &str, shift: bool) -> Option<Self> {
+        let mut chars = key.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Some(Self::Char(c));
+        }
+
+        Some(match key {
+            "Enter" => Self::Enter,
+            "Tab" if shift => Self::BackTab,
+            "Tab" => Self::Tab,
+            "Backspace" => Self::Backspace,
+            "Escape" | "Esc" => Self::Escape,
+            "ArrowUp" | "Up" => Self::Up,
+            "ArrowDown" | "Down" => Self::Down,
+            "ArrowLeft" | "Left" => Self::Left,
+            "ArrowRight" | "Right" => Self::Right,
+            "Home" => Self::Home,
+            "End" => Self::End,
+            "PageUp" => Self::PageUp,
+            "PageDown" => Self::PageDown,
+            "Insert" => Self::Insert,
+            "Delete" | "Del" => Self::Delete,
+            "Spacebar" => Self::Char(' '),
+            f if f.starts_with('F') => match f[1..].parse() {
+                Ok(n) if (1..=24).contains(&n) => Self::F(n),
+                _ => return None,
+            },
+            _ => return None,
+        })
+    }
+}
+
+/// A key press, as reported by [`Terminal::on_key`].
+///
+/// [`Terminal::on_key`]: crate::xterm::Terminal::on_key
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Key {
+    /// The key that was pressed; `None` if it isn't one that [`KeyCode`] has a
+    /// variant for (i.e. a modifier key).
+    pub code: Option<KeyCode>,
+    /// The DOM `key` value (i.e. `"a"` or `"ArrowUp"`).
+    pub dom_key: String,
+    /// The modifiers that were held down.
+    pub modifiers: Modifiers,
+    /// Where on the keyboard the key is.
+    pub location: KeyLocation,
+    /// Whether this is an automatic repeat of a key that's being held down.
+    pub repeat: bool,
+    /// The sequence xterm.js sent to the program for the key press; empty if
+    /// it didn't send anything.
+    pub sequence: String,
+}
+
+impl Key {
+    /// The [`KeyInput`] that corresponds to this key press, if there is one.
+    #[must_use]
+    pub fn input(&self) -> Option<KeyInput> {
+        self.code.map(|code| KeyInput::new(code, self.modifiers))
+    }
+}
+
+impl From<&KeyEventData> for Key {
+    fn from(data: &KeyEventData) -> Self {
+        let event = data.dom_event();
+        let dom_key = event.key();
+
+        let modifiers = Modifiers::from_dom(&event);
+
+        Self {
+            code: KeyCode::from_dom_key(&dom_key, modifiers.shift),
+            dom_key,
+            modifiers,
+            location: KeyLocation::from_dom(event.location()),
+            repeat: event.repeat(),
+            sequence: data.key(),
+        }
+    }
+}
+
+impl KeyEventData {
+    /// Converts this event into a [`Key`].
+    #[must_use]
+    pub fn to_key(&self) -> Key {
+        self.into()
+    }
+}
+
+#[cfg(all(feature = "crossterm-events", any(unix, windows)))]
+#[cfg_attr(
+    all(docs, not(doctest)),
+    doc(cfg(all(feature = "crossterm-events", any(unix, windows))))
+)]
+mod crossterm_conversions {
+    //! Conversions into crossterm's key event types.
+
+    use super::{Key, KeyCode, KeyInput, Modifiers};
+
+    use crossterm::event::{
+        KeyCode as CtKeyCode, KeyEvent as CtKeyEvent,
+        KeyModifiers as CtKeyModifiers,
+    };
+
+    impl From<KeyCode> for CtKeyCode {
+        fn from(code: KeyCode) -> Self {
+            match code {
+                KeyCode::Char(c) => Self::Char(c),
+                KeyCode::Enter => Self::Enter,
+                KeyCode::Tab => Self::Tab,
+                KeyCode::BackTab => Self::BackTab,
+                KeyCode::Backspace => Self::Backspace,
+                KeyCode::Escape => Self::Esc,
+                KeyCode::Up => Self::Up,
+                KeyCode::Down => Self::Down,
+                KeyCode::Left => Self::Left,
+                KeyCode::Right => Self::Right,
+                KeyCode::Home => Self::Home,
+                KeyCode::End => Self::End,
+                KeyCode::PageUp => Self::PageUp,
+                KeyCode::PageDown => Self::PageDown,
+                KeyCode::Insert => Self::Insert,
+                KeyCode::Delete => Self::Delete,
+                KeyCode::F(n) => Self::F(n),
+            }
+        }
+    }
+
+    /// Note: crossterm has no meta modifier so `meta` is dropped.
+    impl From<Modifiers> for CtKeyModifiers {
+        fn from(modifiers: Modifiers) -> Self {
+            let mut out = Self::empty();
+            out.set(Self::SHIFT, modifiers.shift);
+            out.set(Self::ALT, modifiers.alt);
+            out.set(Self::CONTROL, modifiers.ctrl);
+            out
+        }
+    }
+
+    impl From<KeyInput> for CtKeyEvent {
+        fn from(key: KeyInput) -> Self {
+            Self {
+                code: key.code.into(),
+                modifiers: key.modifiers.into(),
+            }
+        }
+    }
+
+    /// Keys without a [`KeyCode`] become [`CtKeyCode::Null`].
+    impl From<&Key> for CtKeyEvent {
+        fn from(key: &Key) -> Self {
+            Self {
+                code: key.code.map_or(CtKeyCode::Null, Into::into),
+                modifiers: key.modifiers.into(),
+            }
+        }
+    }
+}
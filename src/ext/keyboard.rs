@@ -0,0 +1,614 @@
+//! Enhanced keyboard protocols: the [kitty keyboard protocol] and xterm's
+//! [`modifyOtherKeys`].
+//!
+//! The sequences xterm.js sends for keys are ambiguous: `Ctrl+I` and `Tab`
+//! both send `\t`, `Escape` is indistinguishable from the start of an escape
+//! sequence, `Ctrl+Enter` sends the same thing as `Enter` and so on. Programs
+//! like neovim can ask the terminal for a better encoding; this module
+//! implements the two common ones.
+//!
+//! [`KeyboardModes`] is the pure Rust part: it tracks what the program has
+//! asked for (by way of the CSI sequences each protocol uses to query and
+//! enable itself) and encodes keys accordingly. [`KeyboardProtocols`] hooks
+//! it up to a [`Terminal`] using the parser hooks and a custom key event
+//! handler (see [`Terminal::enable_keyboard_protocols`]).
+//!
+//! Only the "disambiguate", "report event types" and "report all keys" kitty
+//! flags change how keys are encoded; the others are tracked (and reported to
+//! programs that query them) but otherwise ignored.
+//!
+//! [kitty keyboard protocol]: https://sw.kovidgoyal.net/kitty/keyboard-protocol/
+//! [`modifyOtherKeys`]: https://invisible-island.net/xterm/modified-keys.html
+
+use super::{EventListener, KeyCode, KeyInput, Modifiers};
+use crate::xterm::{FunctionIdentifier, Terminal};
+use crate::ReadOnlyArray;
+
+use js_sys::Array;
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::KeyboardEvent;
+
+use core::cell::RefCell;
+use core::fmt::{self, Debug};
+use std::rc::Rc;
+
+/// The progressive enhancement flags of the kitty keyboard protocol.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct KittyFlags {
+    /// Report ambiguous keys (i.e. `Escape` or `Ctrl+I`) as escape codes.
+    pub disambiguate: bool,
+    /// Report key repeat and release events.
+    pub report_event_types: bool,
+    /// Report the shifted and base layout keys.
+    pub report_alternate_keys: bool,
+    /// Report all keys (including text and `Enter`, `Tab` and `Backspace`) as
+    /// escape codes.
+    pub report_all_keys: bool,
+    /// Report the text a key produces along with its escape code.
+    pub report_text: bool,
+}
+
+impl KittyFlags {
+    /// No flags: legacy key encoding.
+    pub const NONE: Self = Self {
+        disambiguate: false,
+        report_event_types: false,
+        report_alternate_keys: false,
+        report_all_keys: false,
+        report_text: false,
+    };
+
+    /// Decodes the flags from their bitfield representation; unknown bits are
+    /// ignored.
+    #[must_use]
+    pub fn from_bits(bits: u32) -> Self {
+        Self {
+            disambiguate: bits & 1 != 0,
+            report_event_types: bits & 2 != 0,
+            report_alternate_keys: bits & 4 != 0,
+            report_all_keys: bits & 8 != 0,
+            report_text: bits & 16 != 0,
+        }
+    }
+
+    /// Encodes the flags as a bitfield; the inverse of
+    /// [`KittyFlags::from_bits`].
+    #[must_use]
+    pub fn bits(self) -> u32 {
+        u32::from(self.disambiguate)
+            | (u32::from(self.report_event_types) << 1)
+            | (u32::from(self.report_alternate_keys) << 2)
+            | (u32::from(self.report_all_keys) << 3)
+            | (u32::from(self.report_text) << 4)
+    }
+}
+
+/// The kind of key event being encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyEventType {
+    /// The key was pressed.
+    Press,
+    /// The key is being held down and was automatically repeated.
+    Repeat,
+    /// The key was released.
+    Release,
+}
+
+/// How many entries the kitty flag stack holds before the oldest entries are
+/// evicted (as the protocol suggests, to stop misbehaving programs from using
+/// unbounded memory).
+const KITTY_STACK_LIMIT: usize = 16;
+
+/// The keyboard protocol state a program has asked for.
+///
+/// This is driven by the CSI sequences programs send (see
+/// [`KeyboardModes::handle_csi`]) and decides how keys are encoded (see
+/// [`KeyboardModes::encode`]).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct KeyboardModes {
+    /// The kitty flag stack; the last entry holds the current flags.
+    kitty: Vec<KittyFlags>,
+    /// The `modifyOtherKeys` level (0, 1 or 2).
+    modify_other_keys: u8,
+}
+
+impl KeyboardModes {
+    /// Legacy encoding for everything.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The kitty flags currently in effect.
+    #[must_use]
+    pub fn kitty_flags(&self) -> KittyFlags {
+        self.kitty.last().copied().unwrap_or(KittyFlags::NONE)
+    }
+
+    /// The `modifyOtherKeys` level currently in effect.
+    #[must_use]
+    pub fn modify_other_keys(&self) -> u8 {
+        self.modify_other_keys
+    }
+
+    /// Pushes `flags` onto the kitty flag stack (`CSI > flags u`).
+    pub fn push_kitty_flags(&mut self, flags: KittyFlags) {
+        if self.kitty.len() == KITTY_STACK_LIMIT {
+            let _ = self.kitty.remove(0);
+        }
+
+        self.kitty.push(flags);
+    }
+
+    /// Pops `count` entries off of the kitty flag stack (`CSI < count u`).
+    ///
+    /// Popping more entries than there are resets the flags.
+    pub fn pop_kitty_flags(&mut self, count: usize) {
+        let len = self.kitty.len();
+        self.kitty.truncate(len.saturating_sub(count));
+    }
+
+    /// Modifies the current kitty flags (`CSI = flags ; mode u`).
+    ///
+    /// `mode` 1 replaces the current flags, 2 sets the bits in `flags` and 3
+    /// clears them; other modes are ignored.
+    pub fn set_kitty_flags(&mut self, flags: KittyFlags, mode: u32) {
+        let current = self.kitty_flags().bits();
+        let updated = match mode {
+            1 => flags.bits(),
+            2 => current | flags.bits(),
+            3 => current & !flags.bits(),
+            _ => return,
+        };
+
+        let updated = KittyFlags::from_bits(updated);
+        match self.kitty.last_mut() {
+            Some(top) => *top = updated,
+            None => self.kitty.push(updated),
+        }
+    }
+
+    /// Sets the `modifyOtherKeys` level (`CSI > 4 ; level m`); levels above 2
+    /// are treated as 2.
+    pub fn set_modify_other_keys(&mut self, level: u8) {
+        self.modify_other_keys = level.min(2);
+    }
+
+    /// Handles a CSI sequence with the given prefix (i.e. `'>'`), final byte
+    /// and parameters.
+    ///
+    /// Responses to queries are passed to `reply`, which should send them to
+    /// the program (just like input is).
+    ///
+    /// Returns whether the sequence was one of the keyboard protocol sequences;
+    /// if not nothing is changed.
+    pub fn handle_csi<R: FnMut(&str)>(
+        &mut self,
+        prefix: char,
+        final_byte: char,
+        params: &[u32],
+        mut reply: R,
+    ) -> bool {
+        let param = |idx: usize| params.get(idx).copied();
+
+        match (prefix, final_byte) {
+            ('?', 'u') => {
+                reply(&format!("\x1b[?{}u", self.kitty_flags().bits()));
+            }
+            ('>', 'u') => self
+                .push_kitty_flags(KittyFlags::from_bits(param(0).unwrap_or(0))),
+            ('<', 'u') => {
+                self.pop_kitty_flags(param(0).unwrap_or(1).max(1) as usize);
+            }
+            ('=', 'u') => self.set_kitty_flags(
+                KittyFlags::from_bits(param(0).unwrap_or(0)),
+                param(1).unwrap_or(1),
+            ),
+
+            ('>', 'm') if param(0) == Some(4) => {
+                #[allow(clippy::cast_possible_truncation)]
+                let level = param(1).unwrap_or(0).min(2) as u8;
+                self.set_modify_other_keys(level);
+            }
+            ('?', 'm') if param(0) == Some(4) => {
+                reply(&format!("\x1b[>4;{}m", self.modify_other_keys));
+            }
+
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Encodes a key event using whichever protocol is enabled (kitty takes
+    /// precedence).
+    ///
+    /// Returns `None` if the key should be encoded the legacy way (i.e. by
+    /// xterm.js).
+    #[must_use]
+    pub fn encode(
+        &self,
+        key: KeyInput,
+        event_type: KeyEventType,
+    ) -> Option<String> {
+        let flags = self.kitty_flags();
+
+        if flags == KittyFlags::NONE {
+            match event_type {
+                KeyEventType::Release => None,
+                _ => encode_modify_other_keys(key, self.modify_other_keys),
+            }
+        } else {
+            encode_kitty(key, event_type, flags)
+        }
+    }
+}
+
+/// Gets the kitty key number and final byte for a key.
+fn kitty_key(code: KeyCode) -> (u32, char) {
+    match code {
+        KeyCode::Char(c) => {
+            // Keys are identified by their unshifted (lowercase) character.
+            let mut lower = c.to_lowercase();
+            match (lower.next(), lower.next()) {
+                (Some(l), None) => (u32::from(l), 'u'),
+                _ => (u32::from(c), 'u'),
+            }
+        }
+        KeyCode::Enter => (13, 'u'),
+        KeyCode::Tab | KeyCode::BackTab => (9, 'u'),
+        KeyCode::Backspace => (127, 'u'),
+        KeyCode::Escape => (27, 'u'),
+        KeyCode::Up => (1, 'A'),
+        KeyCode::Down => (1, 'B'),
+        KeyCode::Right => (1, 'C'),
+        KeyCode::Left => (1, 'D'),
+        KeyCode::Home => (1, 'H'),
+        KeyCode::End => (1, 'F'),
+        KeyCode::Insert => (2, '~'),
+        KeyCode::Delete => (3, '~'),
+        KeyCode::PageUp => (5, '~'),
+        KeyCode::PageDown => (6, '~'),
+        KeyCode::F(1) => (1, 'P'),
+        KeyCode::F(2) => (1, 'Q'),
+        KeyCode::F(3) => (13, '~'),
+        KeyCode::F(4) => (1, 'S'),
+        KeyCode::F(n @ 5) => (u32::from(n) + 10, '~'),
+        KeyCode::F(n @ 6..=10) => (u32::from(n) + 11, '~'),
+        KeyCode::F(n @ 11..=12) => (u32::from(n) + 12, '~'),
+        // F13 and up live in the private use area.
+        KeyCode::F(n) => (57363 + u32::from(n), 'u'),
+    }
+}
+
+/// Encodes a key event with the [kitty keyboard protocol].
+///
+/// Returns `None` if the key should be sent as-is (i.e. it's text or a key
+/// whose legacy encoding is unambiguous) or, for releases, not sent at all.
+///
+/// [kitty keyboard protocol]: https://sw.kovidgoyal.net/kitty/keyboard-protocol/
+#[must_use]
+pub fn encode_kitty(
+    key: KeyInput,
+    event_type: KeyEventType,
+    flags: KittyFlags,
+) -> Option<String> {
+    let mut modifiers = key.modifiers;
+    if key.code == KeyCode::BackTab {
+        modifiers.shift = true;
+    }
+
+    let event_type = if flags.report_event_types {
+        event_type
+    } else if event_type == KeyEventType::Release {
+        return None;
+    } else {
+        KeyEventType::Press
+    };
+
+    if !flags.report_all_keys {
+        let text = matches!(key.code, KeyCode::Char(_))
+            && !modifiers.alt
+            && !modifiers.ctrl;
+        let legacy_control = matches!(
+            key.code,
+            KeyCode::Enter | KeyCode::Tab | KeyCode::Backspace
+        );
+
+        // Text (and unmodified Enter, Tab and Backspace) is still sent as-is;
+        // the latter don't report releases unless every key is reported.
+        if (text && !modifiers.meta) || (legacy_control && !modifiers.any()) {
+            return match event_type {
+                KeyEventType::Press | KeyEventType::Repeat => None,
+                KeyEventType::Release if legacy_control => None,
+                KeyEventType::Release => Some(kitty_sequence(
+                    kitty_key(key.code),
+                    modifiers,
+                    event_type,
+                )),
+            };
+        }
+
+        // Keys with a (`CSI ... ~` or `CSI 1 ; ... X`) legacy encoding are
+        // already unambiguous.
+        if kitty_key(key.code).1 != 'u' && event_type == KeyEventType::Press {
+            return None;
+        }
+    }
+
+    Some(kitty_sequence(kitty_key(key.code), modifiers, event_type))
+}
+
+/// Formats a kitty key sequence: `CSI number ; modifiers:event final`.
+fn kitty_sequence(
+    (number, final_byte): (u32, char),
+    modifiers: Modifiers,
+    event_type: KeyEventType,
+) -> String {
+    let event = match event_type {
+        KeyEventType::Press => None,
+        KeyEventType::Repeat => Some(2),
+        KeyEventType::Release => Some(3),
+    };
+
+    let modifiers = modifiers.to_param();
+    let params = match event {
+        Some(event) => format!("{};{}:{}", number, modifiers, event),
+        None if modifiers != 1 => format!("{};{}", number, modifiers),
+        None if number == 1 && final_byte != 'u' => String::new(),
+        None => number.to_string(),
+    };
+
+    format!("\x1b[{}{}", params, final_byte)
+}
+
+/// Encodes a key press with xterm's [`modifyOtherKeys`] at the given level.
+///
+/// Level 1 only encodes modified keys that would otherwise be ambiguous;
+/// level 2 encodes all modified keys except for shifted text. Returns `None`
+/// if the key should be sent as-is.
+///
+/// [`modifyOtherKeys`]: https://invisible-island.net/xterm/modified-keys.html
+#[must_use]
+pub fn encode_modify_other_keys(key: KeyInput, level: u8) -> Option<String> {
+    let modifiers = key.modifiers;
+    if level == 0 || !modifiers.any() {
+        return None;
+    }
+
+    let shift_only = Modifiers {
+        shift: true,
+        ..Modifiers::NONE
+    };
+
+    let code = match key.code {
+        KeyCode::Char(_) if modifiers == shift_only => return None,
+        KeyCode::Char(c) if level == 1 => {
+            // Control characters and `Alt` prefixed keys are well known;
+            // leave them be.
+            let well_known = (c.is_ascii_alphabetic() && !modifiers.shift)
+                || " @[\\]^_?".contains(c)
+                || !modifiers.ctrl;
+
+            if well_known {
+                return None;
+            }
+
+            u32::from(c)
+        }
+        KeyCode::Char(c) => u32::from(c),
+
+        KeyCode::Enter => 13,
+        KeyCode::Escape => 27,
+        KeyCode::Tab | KeyCode::Backspace | KeyCode::BackTab if level == 1 => {
+            return None
+        }
+        KeyCode::Tab | KeyCode::BackTab => 9,
+        KeyCode::Backspace => 127,
+
+        // Everything else already has an encoding that carries modifiers.
+        _ => return None,
+    };
+
+    Some(format!("\x1b[27;{};{}~", modifiers.to_param(), code))
+}
+
+/// Flattens the parameters xterm.js hands CSI handlers, dropping
+/// sub-parameters.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub(super) fn csi_params(params: &ReadOnlyArray<JsValue>) -> Vec<u32> {
+    params
+        .typed_iter()
+        .map(|param| {
+            let param = if Array::is_array(&param) {
+                param.unchecked_into::<Array>().get(0)
+            } else {
+                param
+            };
+
+            param.as_f64().map_or(0, |p| p as u32)
+        })
+        .collect()
+}
+
+/// A CSI handler, as taken by [`Parser::register_csi_handler`].
+///
+/// [`Parser::register_csi_handler`]: crate::xterm::Parser::register_csi_handler
+pub(super) type CsiHandler = dyn FnMut(ReadOnlyArray<JsValue>) -> bool;
+
+/// The kitty keyboard protocol and `modifyOtherKeys`, hooked up to a
+/// [`Terminal`].
+///
+/// Created with [`Terminal::enable_keyboard_protocols`]. Dropping this removes
+/// the parser hooks and detaches the key event handler.
+pub struct KeyboardProtocols {
+    /// The terminal the handlers are attached to.
+    terminal: Terminal,
+    /// The current modes.
+    modes: Rc<RefCell<KeyboardModes>>,
+    /// The CSI handlers that drive `modes`.
+    handlers: Vec<EventListener<CsiHandler>>,
+    /// The custom key event handler.
+    key_handler: Closure<dyn FnMut(KeyboardEvent) -> bool>,
+}
+
+impl Debug for KeyboardProtocols {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("KeyboardProtocols")
+            .field("terminal", &self.terminal)
+            .field("modes", &self.modes)
+            .field("handlers", &self.handlers)
+            .field("key_handler", &self.key_handler)
+            .finish()
+    }
+}
+
+impl KeyboardProtocols {
+    /// The modes the program has currently asked for.
+    #[must_use]
+    pub fn modes(&self) -> KeyboardModes {
+        self.modes.borrow().clone()
+    }
+}
+
+thread_local! {
+    /// A custom key event handler that lets every key through; used to detach
+    /// our handler.
+    ///
+    /// This is a Rust closure (rather than a `Function` made from source) so
+    /// that it works on pages whose CSP doesn't allow `eval`.
+    static PASS_THROUGH: Closure<dyn FnMut(KeyboardEvent) -> bool> =
+        Closure::wrap(Box::new(|_| true));
+}
+
+impl Drop for KeyboardProtocols {
+    fn drop(&mut self) {
+        // There's no way to detach a custom key event handler so we replace
+        // ours with one that lets everything through.
+        PASS_THROUGH.with(|handler| {
+            self.terminal.attach_custom_key_event_handler(handler);
+        });
+    }
+}
+
+impl Terminal {
+    /// Enables the kitty keyboard protocol and `modifyOtherKeys` for this
+    /// terminal.
+    ///
+    /// Programs running in the terminal can now query and enable either
+    /// protocol; once enabled, keys are encoded accordingly and handed to
+    /// `send` instead of going through [`Terminal::on_data`]. Query responses
+    /// are also handed to `send`. `send` should deliver the data to the
+    /// program the same way input from [`Terminal::on_data`] is delivered.
+    ///
+    /// This attaches a custom key event handler (see
+    /// [`Terminal::attach_custom_key_event_handler`]), replacing any existing
+    /// one.
+    pub fn enable_keyboard_protocols<S>(&self, send: S) -> KeyboardProtocols
+    where
+        S: FnMut(&str) + 'static,
+    {
+        let modes = Rc::new(RefCell::new(KeyboardModes::new()));
+        let send = Rc::new(RefCell::new(send));
+
+        let parser = self.parser();
+        let sequences = [
+            ('?', 'u'),
+            ('>', 'u'),
+            ('<', 'u'),
+            ('=', 'u'),
+            ('>', 'm'),
+            ('?', 'm'),
+        ];
+        let handlers = sequences
+            .iter()
+            .map(|&(prefix, final_byte)| {
+                let modes = modes.clone();
+                let send = send.clone();
+
+                let handler = move |params: ReadOnlyArray<JsValue>| {
+                    let mut replies = String::new();
+                    let handled = modes.borrow_mut().handle_csi(
+                        prefix,
+                        final_byte,
+                        &csi_params(&params),
+                        |reply| replies.push_str(reply),
+                    );
+
+                    if !replies.is_empty() {
+                        (send.borrow_mut())(&replies);
+                    }
+
+                    handled
+                };
+
+                let handler: Box<CsiHandler> = Box::new(handler);
+                EventListener::new(Closure::wrap(handler), |closure| {
+                    parser.register_csi_handler(
+                        FunctionIdentifier::new(
+                            Some(prefix.to_string()),
+                            None,
+                            final_byte.to_string(),
+                        ),
+                        closure,
+                    )
+                })
+            })
+            .collect();
+
+        let key_modes = modes.clone();
+        let mut suppress_keypress = false;
+        let key_handler = move |event: KeyboardEvent| {
+            let event_type = match &*event.type_() {
+                "keydown" if event.repeat() => KeyEventType::Repeat,
+                "keydown" => KeyEventType::Press,
+                "keyup" => KeyEventType::Release,
+                // The `keypress` that follows a `keydown` we handled shouldn't
+                // be processed either.
+                _ => return !suppress_keypress,
+            };
+
+            if event_type != KeyEventType::Release {
+                suppress_keypress = false;
+            }
+
+            if event.is_composing() {
+                return true;
+            }
+
+            let modifiers = Modifiers::from_dom(&event);
+            let code =
+                match KeyCode::from_dom_key(&event.key(), modifiers.shift) {
+                    Some(code) => code,
+                    None => return true,
+                };
+
+            let encoded = key_modes
+                .borrow()
+                .encode(KeyInput::new(code, modifiers), event_type);
+
+            match encoded {
+                Some(sequence) => {
+                    event.prevent_default();
+                    suppress_keypress = event_type != KeyEventType::Release;
+                    (send.borrow_mut())(&sequence);
+                    false
+                }
+                None => true,
+            }
+        };
+
+        let key_handler: Box<dyn FnMut(KeyboardEvent) -> bool> =
+            Box::new(key_handler);
+        let key_handler = Closure::wrap(key_handler);
+        self.attach_custom_key_event_handler(&key_handler);
+
+        KeyboardProtocols {
+            terminal: self.clone(),
+            modes,
+            handlers,
+            key_handler,
+        }
+    }
+}
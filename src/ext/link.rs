@@ -0,0 +1,994 @@
+//! Link providers and some ready-made link detectors.
+//!
+//! [`XtermLinkProvider`] is the Rust dual of the [`LinkProvider`] interface
+//! (and [`XtermLink`] of [`Link`]).
+//! Most users won't need to implement it directly though; implementing
+//! [`LinkDetector`] (or using one of the detectors in this module) and handing
+//! it to [`Terminal::register_link_detector`] takes care of reading the buffer,
+//! joining wrapped lines, and turning whatever is found into cell ranges.
+//!
+//! The detectors themselves operate on plain `&str`s so the matching logic
+//! ([`find_urls`], [`find_file_locations`]) can be used (and tested) without a
+//! terminal.
+//!
+//! The older (and deprecated) link matcher API is also wrapped; see
+//! [`Terminal::register_regex_link_matcher`].
+//!
+//! [`Link`]: crate::xterm::Link
+//! [`LinkProvider`]: crate::xterm::LinkProvider
+
+use super::{
+    interface, object, DisposableWrapper, HandleRegistration, IntoJsInterface,
+};
+use crate::xterm::{
+    Buffer, BufferCellPosition, BufferRange, Link, LinkProvider, Str, Terminal,
+    ViewportRange, ViewportRangePosition,
+};
+
+use js_sys::{Array, Function, Object, Reflect, RegExp};
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::MouseEvent;
+
+use std::fmt::{self, Debug};
+use std::ops::Range;
+use std::rc::Rc;
+
+interface! {
+    #[allow(clippy::module_name_repetitions)]
+    pub trait XtermLinkProvider mirrors LinkProvider {
+        /// Provides links for the buffer line (1-based).
+        ///
+        /// `callback` should be called with an array of [`Link`]s or with
+        /// `undefined` if there are no links on the line.
+        fn provide_links(&self, buffer_line_number: u32, callback: Function);
+    }
+}
+
+interface! {
+    #[allow(clippy::module_name_repetitions)]
+    pub trait XtermLink mirrors Link {
+        /// The buffer range of the link.
+        readonly range: BufferRange;
+
+        /// The text of the link.
+        readonly text: Str;
+
+        /// Called when the link is activated.
+        fn activate(&self, event: MouseEvent, text: Str);
+
+        /// Called when the mouse hovers over the link.
+        #[optional]
+        fn hover(&self, event: MouseEvent, text: Str);
+
+        /// Called when the mouse leaves the link.
+        #[optional]
+        fn leave(&self, event: MouseEvent, text: Str);
+    }
+}
+
+impl BufferCellPosition {
+    /// Constructs a new [`BufferCellPosition`].
+    ///
+    /// Both coordinates are 1-based.
+    #[must_use]
+    pub fn new(x: u16, y: u32) -> Self {
+        object! { x: JsValue::from(x), y: JsValue::from(y) }.unchecked_into()
+    }
+}
+
+impl BufferRange {
+    /// Constructs a new [`BufferRange`].
+    ///
+    /// Note that `end` is inclusive.
+    #[must_use]
+    pub fn new(start: &BufferCellPosition, end: &BufferCellPosition) -> Self {
+        object! { start: start, end: end }.unchecked_into()
+    }
+}
+
+impl Link {
+    /// Constructs a new [`Link`] that calls `activate` (a JS function that
+    /// takes a `MouseEvent` and the text of the link) when it's activated.
+    #[must_use]
+    pub fn new(range: &BufferRange, text: &str, activate: &Function) -> Self {
+        object! {
+            range: range,
+            text: JsValue::from_str(text),
+            activate: activate,
+        }
+        .unchecked_into()
+    }
+}
+
+/// Something that can find links in a line of text.
+///
+/// See [`Terminal::register_link_detector`].
+#[allow(clippy::module_name_repetitions)]
+pub trait LinkDetector {
+    /// Finds the links in `text`, returning the byte range of each link.
+    ///
+    /// `text` is the contents of a logical line: a buffer line joined with all
+    /// the lines that are wrapped onto it.
+    fn detect(&self, text: &str) -> Vec<Range<usize>>;
+
+    /// Called when one of the links this detector found is activated.
+    ///
+    /// `text` is the text of the link.
+    fn activate(&self, event: &MouseEvent, text: &str);
+}
+
+/// Detects `http`, `https`, and `file` URLs.
+///
+/// Trailing punctuation (i.e. the period at the end of a sentence) and
+/// unbalanced closing brackets are not considered part of the URL.
+pub struct UrlDetector<F: Fn(&MouseEvent, &str)> {
+    /// Called with the URL when a link is activated.
+    handler: F,
+}
+
+impl<F: Fn(&MouseEvent, &str)> Debug for UrlDetector<F> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct(core::any::type_name::<Self>())
+            .field("handler", &core::any::type_name::<F>())
+            .finish()
+    }
+}
+
+impl<F: Fn(&MouseEvent, &str)> UrlDetector<F> {
+    /// Creates a URL detector that calls `handler` with the URL when a link is
+    /// activated.
+    pub fn new(handler: F) -> Self {
+        Self { handler }
+    }
+}
+
+impl<F: Fn(&MouseEvent, &str)> LinkDetector for UrlDetector<F> {
+    fn detect(&self, text: &str) -> Vec<Range<usize>> {
+        find_urls(text)
+    }
+
+    fn activate(&self, event: &MouseEvent, text: &str) {
+        (self.handler)(event, text);
+    }
+}
+
+/// A compiler-style reference to a position in a file (i.e.
+/// `src/lib.rs:12:5`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FileLocation {
+    /// The path, exactly as it appeared in the text.
+    pub path: String,
+    /// The line number.
+    pub line: u32,
+    /// The column number, if one was given.
+    pub column: Option<u32>,
+}
+
+impl FileLocation {
+    /// Parses a [`FileLocation`] out of `text`; all of `text` must be the
+    /// location.
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        find_file_locations(text)
+            .into_iter()
+            .next()
+            .filter(|(range, _)| *range == (0..text.len()))
+            .map(|(_, loc)| loc)
+    }
+}
+
+/// Detects compiler-style `path/to/file.rs:line:column` references.
+///
+/// See [`find_file_locations`] for what's considered a path.
+pub struct FileLocationDetector<F: Fn(&MouseEvent, &FileLocation)> {
+    /// Called with the location when a link is activated.
+    handler: F,
+}
+
+impl<F: Fn(&MouseEvent, &FileLocation)> Debug for FileLocationDetector<F> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct(core::any::type_name::<Self>())
+            .field("handler", &core::any::type_name::<F>())
+            .finish()
+    }
+}
+
+impl<F: Fn(&MouseEvent, &FileLocation)> FileLocationDetector<F> {
+    /// Creates a file location detector that calls `handler` with the location
+    /// when a link is activated.
+    pub fn new(handler: F) -> Self {
+        Self { handler }
+    }
+}
+
+impl<F: Fn(&MouseEvent, &FileLocation)> LinkDetector
+    for FileLocationDetector<F>
+{
+    fn detect(&self, text: &str) -> Vec<Range<usize>> {
+        find_file_locations(text)
+            .into_iter()
+            .map(|(range, _)| range)
+            .collect()
+    }
+
+    fn activate(&self, event: &MouseEvent, text: &str) {
+        if let Some(loc) = FileLocation::parse(text) {
+            (self.handler)(event, &loc);
+        }
+    }
+}
+
+#[wasm_bindgen]
+extern "C" {
+    /// `RegExp(pattern, flags)`; unlike [`RegExp::new`] this doesn't throw on
+    /// invalid patterns.
+    #[wasm_bindgen(catch, js_name = RegExp)]
+    fn try_regexp(pattern: &str, flags: &str) -> Result<RegExp, JsValue>;
+}
+
+/// Compiles a regular expression to a JS [`RegExp`].
+///
+/// The pattern is mostly passed through as is, but Rust style named groups
+/// (`(?P<name>...)`) are rewritten to the JS syntax (`(?<name>...)`).
+fn compile_regex(pattern: &str, flags: &str) -> Result<RegExp, JsValue> {
+    try_regexp(&pattern.replace("(?P<", "(?<"), flags)
+}
+
+/// Detects matches of a user supplied regular expression.
+///
+/// Matching is done by the JS regex engine.
+pub struct RegexDetector<F: Fn(&MouseEvent, &str)> {
+    /// The (global) regular expression.
+    regex: RegExp,
+    /// Called with the matched text when a link is activated.
+    handler: F,
+}
+
+impl<F: Fn(&MouseEvent, &str)> Debug for RegexDetector<F> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct(core::any::type_name::<Self>())
+            .field("regex", &self.regex)
+            .field("handler", &core::any::type_name::<F>())
+            .finish()
+    }
+}
+
+impl<F: Fn(&MouseEvent, &str)> RegexDetector<F> {
+    /// Creates a detector for `pattern` that calls `handler` with the matched
+    /// text when a link is activated.
+    ///
+    /// # Errors
+    ///
+    /// Returns the `SyntaxError` thrown by `RegExp` if `pattern` is not a
+    /// valid regular expression.
+    pub fn new(pattern: &str, handler: F) -> Result<Self, JsValue> {
+        Ok(Self {
+            regex: compile_regex(pattern, "gu")?,
+            handler,
+        })
+    }
+}
+
+impl<F: Fn(&MouseEvent, &str)> LinkDetector for RegexDetector<F> {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn detect(&self, text: &str) -> Vec<Range<usize>> {
+        let mut found = Vec::new();
+        self.regex.set_last_index(0);
+
+        while let Some(m) = self.regex.exec(text) {
+            let matched = m.get(0).as_string().unwrap_or_default();
+            let idx = Reflect::get(&m, &JsValue::from_str("index"))
+                .ok()
+                .and_then(|i| i.as_f64())
+                .map_or(0, |i| i as usize);
+
+            if matched.is_empty() {
+                // Don't get stuck on empty matches.
+                self.regex.set_last_index(self.regex.last_index() + 1);
+                continue;
+            }
+
+            let start = utf16_to_byte_offset(text, idx);
+            found.push(start..(start + matched.len()));
+        }
+
+        found
+    }
+
+    fn activate(&self, event: &MouseEvent, text: &str) {
+        (self.handler)(event, text);
+    }
+}
+
+/// Converts an index into the UTF-16 encoding of `text` (what JS uses) into a
+/// byte offset into `text`.
+fn utf16_to_byte_offset(text: &str, idx: usize) -> usize {
+    let mut units = 0;
+    for (offset, c) in text.char_indices() {
+        if units >= idx {
+            return offset;
+        }
+        units += c.len_utf16();
+    }
+
+    text.len()
+}
+
+/// The schemes [`find_urls`] looks for.
+const URL_SCHEMES: &[&str] = &["https://", "http://", "file://"];
+
+/// Characters that can't appear in a URL (in addition to whitespace and
+/// control characters).
+fn is_url_char(c: char) -> bool {
+    !(c.is_whitespace()
+        || c.is_control()
+        || matches!(c, '"' | '\'' | '<' | '>' | '`'))
+}
+
+/// Returns the length of `url` once trailing punctuation and unbalanced
+/// closing brackets are removed.
+fn trim_url(url: &str) -> usize {
+    let mut url = url;
+
+    while let Some(last) = url.chars().next_back() {
+        let open = match last {
+            '.' | ',' | ';' | ':' | '!' | '?' | '*' => None,
+            ')' => Some('('),
+            ']' => Some('['),
+            '}' => Some('{'),
+            _ => break,
+        };
+
+        if let Some(open) = open {
+            let opened = url.matches(open).count();
+            let closed = url.matches(last).count();
+            if closed <= opened {
+                break;
+            }
+        }
+
+        url = &url[..url.len() - last.len_utf8()];
+    }
+
+    url.len()
+}
+
+/// Finds `http`, `https`, and `file` URLs in `text`.
+///
+/// Returns the byte range of each URL.
+#[must_use]
+pub fn find_urls(text: &str) -> Vec<Range<usize>> {
+    let mut found = Vec::new();
+    let mut pos = 0;
+
+    while pos < text.len() {
+        let next = URL_SCHEMES
+            .iter()
+            .filter_map(|s| text[pos..].find(s).map(|i| (pos + i, s.len())))
+            .min_by_key(|&(start, _)| start);
+
+        let (start, scheme_len) = match next {
+            Some(next) => next,
+            None => break,
+        };
+
+        let at_boundary = text[..start]
+            .chars()
+            .next_back()
+            .map_or(true, |c| !c.is_alphanumeric());
+        let len = text[start..]
+            .find(|c| !is_url_char(c))
+            .unwrap_or(text.len() - start);
+        let end = start + trim_url(&text[start..(start + len)]);
+
+        if at_boundary && end > start + scheme_len {
+            found.push(start..end);
+            pos = end;
+        } else {
+            pos = start + scheme_len;
+        }
+    }
+
+    found
+}
+
+/// Characters that can be part of a path in [`find_file_locations`].
+fn is_path_char(c: char) -> bool {
+    c.is_alphanumeric()
+        || matches!(c, '/' | '\\' | '.' | '_' | '-' | '~' | '+' | '@')
+}
+
+/// Whether `path` is plausibly a path to a file: it has to either contain a
+/// directory separator or have a file extension (that starts with a letter).
+fn looks_like_path(path: &str) -> bool {
+    if path.is_empty() || path.starts_with("//") {
+        return false;
+    }
+
+    let file_name = path.rsplit(&['/', '\\'][..]).next().unwrap();
+    let has_extension = file_name
+        .rfind('.')
+        .filter(|&dot| dot > 0)
+        .and_then(|dot| file_name[(dot + 1)..].chars().next())
+        .map_or(false, |c| c.is_ascii_alphabetic());
+
+    has_extension || path.contains(&['/', '\\'][..])
+}
+
+/// Parses the run of ASCII digits at the start of `text`, returning the number
+/// and the number of bytes it took up.
+fn leading_number(text: &str) -> Option<(u32, usize)> {
+    let len = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    text[..len].parse().ok().map(|n| (n, len))
+}
+
+/// Finds compiler-style file references (`path:line` and `path:line:column`)
+/// in `text`.
+///
+/// Paths are runs of alphanumeric characters and `/\._-~+@` that contain a
+/// directory separator or end in a file extension; this catches things like
+/// `src/main.rs:3:5` and `lib.rs:12` but not timestamps like `12:30:45`.
+///
+/// Returns the byte range of each reference along with the parsed location.
+#[must_use]
+pub fn find_file_locations(text: &str) -> Vec<(Range<usize>, FileLocation)> {
+    let mut found = Vec::new();
+    let mut floor = 0;
+    let mut pos = 0;
+
+    while let Some(off) = text[pos..].find(':') {
+        let colon = pos + off;
+        pos = colon + 1;
+
+        let path_start = text[floor..colon]
+            .char_indices()
+            .rev()
+            .find(|&(_, c)| !is_path_char(c))
+            .map_or(floor, |(i, c)| floor + i + c.len_utf8());
+        let path = &text[path_start..colon];
+
+        if !looks_like_path(path) {
+            continue;
+        }
+
+        let (line, len) = match leading_number(&text[pos..]) {
+            Some(n) => n,
+            None => continue,
+        };
+        let mut end = pos + len;
+
+        let mut column = None;
+        if text[end..].starts_with(':') {
+            if let Some((col, len)) = leading_number(&text[(end + 1)..]) {
+                column = Some(col);
+                end += 1 + len;
+            }
+        }
+
+        if text[end..]
+            .chars()
+            .next()
+            .map_or(false, char::is_alphanumeric)
+        {
+            continue;
+        }
+
+        found.push((
+            path_start..end,
+            FileLocation {
+                path: path.to_string(),
+                line,
+                column,
+            },
+        ));
+
+        floor = end;
+        pos = end;
+    }
+
+    found
+}
+
+/// A cell in a [`LogicalLine`].
+#[derive(Debug, Clone, Copy)]
+struct LineCell {
+    /// Byte offset of the cell's contents in [`LogicalLine::text`].
+    offset: usize,
+    /// The (0-based) column of the cell.
+    x: u16,
+    /// The (0-based) buffer line the cell is in.
+    y: u32,
+    /// The width of the cell's contents.
+    width: u16,
+}
+
+/// A buffer line joined with the lines that are wrapped onto it.
+#[derive(Debug, Default)]
+struct LogicalLine {
+    /// The text of the line.
+    text: String,
+    /// The cells that make up the line, in order.
+    cells: Vec<LineCell>,
+}
+
+impl LogicalLine {
+    /// Reads the logical line that buffer line `y` (0-based) is a part of.
+    fn read(buffer: &Buffer, y: u32) -> Option<Self> {
+        let mut first = y;
+        while first > 0 && buffer.get_line(first)?.is_wrapped() {
+            first -= 1;
+        }
+
+        let mut last = y;
+        while buffer.get_line(last + 1).map_or(false, |l| l.is_wrapped()) {
+            last += 1;
+        }
+
+        let scratch = buffer.get_null_cell();
+        let mut line = Self::default();
+        for y in first..=last {
+            let row = buffer.get_line(y)?;
+
+            for x in 0..row.length() {
+                let cell = row.get_cell(x, Some(scratch.clone()))?;
+
+                // The cell following a wide character is empty and has a width
+                // of 0; the wide character before it covers it.
+                let width = u16::from(cell.get_width());
+                if width == 0 {
+                    continue;
+                }
+
+                line.cells.push(LineCell {
+                    offset: line.text.len(),
+                    x,
+                    y,
+                    width,
+                });
+
+                match cell.get_chars() {
+                    c if c.is_empty() => line.text.push(' '),
+                    c => line.text.push_str(&c),
+                }
+            }
+        }
+
+        Some(line)
+    }
+
+    /// Finds the cell that the byte at `offset` belongs to.
+    fn cell_at(&self, offset: usize) -> Option<LineCell> {
+        let idx = match self.cells.binary_search_by_key(&offset, |c| c.offset) {
+            Ok(idx) => idx,
+            Err(idx) => idx.checked_sub(1)?,
+        };
+
+        self.cells.get(idx).copied()
+    }
+
+    /// Converts a byte range in the text into the range of cells it spans.
+    ///
+    /// Returns the first and last cell.
+    fn cells_for(&self, bytes: &Range<usize>) -> Option<(LineCell, LineCell)> {
+        if bytes.start >= bytes.end {
+            return None;
+        }
+
+        Some((self.cell_at(bytes.start)?, self.cell_at(bytes.end - 1)?))
+    }
+}
+
+/// A [`XtermLinkProvider`] that runs a [`LinkDetector`] over the terminal's
+/// active buffer.
+struct DetectorLinkProvider<D: LinkDetector> {
+    /// The terminal whose buffer we're reading.
+    terminal: Terminal,
+    /// The detector that finds the actual links.
+    detector: Rc<D>,
+    /// JS function that calls [`LinkDetector::activate`]; shared by all the
+    /// links this provider produces.
+    activate: Rc<Closure<dyn FnMut(MouseEvent, Str)>>,
+}
+
+impl<D: LinkDetector> Clone for DetectorLinkProvider<D> {
+    fn clone(&self) -> Self {
+        Self {
+            terminal: self.terminal.clone(),
+            detector: self.detector.clone(),
+            activate: self.activate.clone(),
+        }
+    }
+}
+
+impl<D: LinkDetector> DetectorLinkProvider<D> {
+    /// Finds the links that touch buffer line `y` (0-based).
+    fn links(&self, y: u32) -> Vec<Link> {
+        let line = match LogicalLine::read(&self.terminal.buffer().active(), y)
+        {
+            Some(line) => line,
+            None => return Vec::new(),
+        };
+
+        self.detector
+            .detect(&line.text)
+            .into_iter()
+            .filter_map(|bytes| {
+                let (first, last) = line.cells_for(&bytes)?;
+                if y < first.y || y > last.y {
+                    return None;
+                }
+
+                let range = BufferRange::new(
+                    &BufferCellPosition::new(first.x + 1, first.y + 1),
+                    &BufferCellPosition::new(last.x + last.width, last.y + 1),
+                );
+
+                Some(Link::new(
+                    &range,
+                    &line.text[bytes],
+                    self.activate.as_ref().as_ref().unchecked_ref(),
+                ))
+            })
+            .collect()
+    }
+}
+
+impl<D: LinkDetector + 'static> XtermLinkProvider for DetectorLinkProvider<D> {
+    fn provide_links(&self, buffer_line_number: u32, callback: Function) {
+        let links = self.links(buffer_line_number.saturating_sub(1));
+
+        let links = if links.is_empty() {
+            JsValue::UNDEFINED
+        } else {
+            links.into_iter().collect::<Array>().into()
+        };
+
+        let _ = callback.call1(&JsValue::NULL, &links);
+    }
+}
+
+/// A validation callback for a link matcher; see
+/// [`RegexLinkMatcherOptions::validate`].
+type ValidationCallback = dyn FnMut(&str) -> bool;
+
+/// A tooltip callback for a link matcher; see
+/// [`RegexLinkMatcherOptions::on_tooltip`].
+type TooltipCallback = dyn FnMut(&MouseEvent, &str, &ViewportRange) -> bool;
+
+/// A `willLinkActivate` callback for a link matcher; see
+/// [`RegexLinkMatcherOptions::will_link_activate`].
+type ActivateCallback = dyn FnMut(&MouseEvent, &str) -> bool;
+
+/// Options for [`Terminal::register_regex_link_matcher`].
+///
+/// This is [`LinkMatcherOptions`] with Rust closures for the callbacks (all of
+/// which are optional). The callbacks are owned by the [`LinkMatcher`] that's
+/// registered with them and are freed along with it.
+///
+/// [`LinkMatcherOptions`]: crate::xterm::LinkMatcherOptions
+#[derive(Default)]
+#[allow(clippy::module_name_repetitions)]
+pub struct RegexLinkMatcherOptions {
+    /// The index of the link from the `regex.match(text)` call.
+    match_index: Option<u32>,
+    /// The priority of the link matcher.
+    priority: Option<i16>,
+    /// Decides whether a match is a link.
+    validation: Option<Box<ValidationCallback>>,
+    /// Called when the mouse hovers over a link.
+    tooltip: Option<Box<TooltipCallback>>,
+    /// Called when the mouse leaves a link.
+    leave: Option<Box<dyn FnMut()>>,
+    /// Decides whether a mouse event activates a link.
+    will_link_activate: Option<Box<ActivateCallback>>,
+}
+
+impl Debug for RegexLinkMatcherOptions {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("RegexLinkMatcherOptions")
+            .field("match_index", &self.match_index)
+            .field("priority", &self.priority)
+            .field("validation", &self.validation.is_some())
+            .field("tooltip", &self.tooltip.is_some())
+            .field("leave", &self.leave.is_some())
+            .field("will_link_activate", &self.will_link_activate.is_some())
+            .finish()
+    }
+}
+
+impl RegexLinkMatcherOptions {
+    /// Options with none of the callbacks set; the same as the defaults
+    /// xterm.js uses.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses capture group `match_index` of the regular expression as the link
+    /// (rather than the whole match).
+    #[must_use]
+    pub fn match_index(mut self, match_index: u32) -> Self {
+        self.match_index = Some(match_index);
+        self
+    }
+
+    /// Sets the priority of the matcher; matchers are evaluated from the
+    /// highest priority to the lowest. The default is 0.
+    #[must_use]
+    pub fn priority(mut self, priority: i16) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Only makes links out of the matches `validate` returns `true` for.
+    #[must_use]
+    pub fn validate<F>(mut self, validate: F) -> Self
+    where
+        F: FnMut(&str) -> bool + 'static,
+    {
+        self.validation = Some(Box::new(validate));
+        self
+    }
+
+    /// Calls `on_tooltip` when the mouse hovers over a link for a while (see
+    /// [`TerminalOptions::link_tooltip_hover_duration`]).
+    ///
+    /// Returning `false` lets xterm.js show its own tooltip.
+    ///
+    /// [`TerminalOptions::link_tooltip_hover_duration`]: crate::xterm::TerminalOptions::link_tooltip_hover_duration
+    #[must_use]
+    pub fn on_tooltip<F>(mut self, on_tooltip: F) -> Self
+    where
+        F: FnMut(&MouseEvent, &str, &ViewportRange) -> bool + 'static,
+    {
+        self.tooltip = Some(Box::new(on_tooltip));
+        self
+    }
+
+    /// Calls `on_leave` when the mouse leaves a link (even if the tooltip
+    /// callback hasn't fired for it yet).
+    #[must_use]
+    pub fn on_leave<F: FnMut() + 'static>(mut self, on_leave: F) -> Self {
+        self.leave = Some(Box::new(on_leave));
+        self
+    }
+
+    /// Only activates a link on the mouse events `will_link_activate` returns
+    /// `true` for (i.e. to require a modifier key); other events propagate as
+    /// usual.
+    #[must_use]
+    pub fn will_link_activate<F>(mut self, will_link_activate: F) -> Self
+    where
+        F: FnMut(&MouseEvent, &str) -> bool + 'static,
+    {
+        self.will_link_activate = Some(Box::new(will_link_activate));
+        self
+    }
+}
+
+/// Reads a viewport range that xterm.js made (xterm.js hands us a plain object
+/// rather than a [`ViewportRange`]).
+fn read_viewport_range(range: &JsValue) -> ViewportRange {
+    // Saturating casts; missing coordinates become 0.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let position = |key: &str| {
+        let position =
+            Reflect::get(range, &JsValue::from_str(key)).unwrap_or_default();
+        let coordinate = |key: &str| {
+            Reflect::get(&position, &JsValue::from_str(key))
+                .ok()
+                .and_then(|c| c.as_f64())
+                .map_or(0, |c| c as u16)
+        };
+
+        ViewportRangePosition::new(coordinate("x"), coordinate("y"))
+    };
+
+    ViewportRange::new(position("start"), position("end"))
+}
+
+/// A registered link matcher.
+///
+/// Dropping this deregisters the matcher and then frees its handler and the
+/// callbacks it was registered with.
+pub struct LinkMatcher {
+    /// The terminal the matcher is registered with.
+    terminal: Terminal,
+    /// The ID xterm.js gave the matcher.
+    id: u32,
+    /// The handler; this must outlive the registration.
+    handler: Closure<dyn FnMut(MouseEvent, Str)>,
+    /// The callbacks from the matcher's options; these must also outlive the
+    /// registration.
+    callbacks: Vec<Box<dyn Debug>>,
+}
+
+impl Debug for LinkMatcher {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("LinkMatcher")
+            .field("terminal", &self.terminal)
+            .field("id", &self.id)
+            .field("handler", &self.handler)
+            .field("callbacks", &self.callbacks)
+            .finish()
+    }
+}
+
+impl LinkMatcher {
+    /// The ID xterm.js gave the matcher.
+    #[must_use]
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+impl Drop for LinkMatcher {
+    #[allow(deprecated)]
+    fn drop(&mut self) {
+        self.terminal.deregister_link_matcher(self.id);
+
+        // Only now that xterm.js can't call them are the closures freed.
+        self.callbacks.clear();
+    }
+}
+
+#[wasm_bindgen]
+extern "C" {
+    /// [`Terminal::register_link_matcher`], but with the options as a JS
+    /// object so that the callbacks can be left out.
+    #[wasm_bindgen(method, js_name = registerLinkMatcher)]
+    fn register_link_matcher_with_object(
+        this: &Terminal,
+        regex: &RegExp,
+        handler: &Closure<dyn FnMut(MouseEvent, Str)>,
+        options: &Object,
+    ) -> u32;
+}
+
+impl Terminal {
+    /// Registers a link provider.
+    ///
+    /// This supports [Rust defined providers](XtermLinkProvider) as well as
+    /// [JS defined providers](LinkProvider) and is otherwise identical to
+    /// [`Terminal::register_link_provider`].
+    ///
+    /// Dropping the returned [`DisposableWrapper`] unregisters the provider
+    /// and then frees it (if it's a Rust provider).
+    pub fn register_xterm_link_provider<P>(
+        &self,
+        provider: P,
+    ) -> DisposableWrapper<HandleRegistration<LinkProvider>>
+    where
+        P: XtermLinkProvider + 'static,
+    {
+        let handle = provider.into_js_handle();
+        let registration = self.register_link_provider(handle.object().clone());
+
+        HandleRegistration::new(registration, handle).into()
+    }
+
+    /// Registers a link provider that uses a [`LinkDetector`] to find links.
+    ///
+    /// The detector is given the text of the (logical) line that the line
+    /// xterm.js asks about is a part of, so links that span wrapped lines are
+    /// found; the byte ranges the detector returns are mapped back to the
+    /// cells they occupy (wide characters included).
+    ///
+    /// Dropping the returned [`DisposableWrapper`] unregisters the provider.
+    pub fn register_link_detector<D: LinkDetector + 'static>(
+        &self,
+        detector: D,
+    ) -> DisposableWrapper<HandleRegistration<LinkProvider>> {
+        let detector = Rc::new(detector);
+
+        let activate: Box<dyn FnMut(MouseEvent, Str)> = {
+            let detector = detector.clone();
+            Box::new(move |event, text| detector.activate(&event, &text))
+        };
+
+        self.register_xterm_link_provider(DetectorLinkProvider {
+            terminal: self.clone(),
+            detector,
+            activate: Rc::new(Closure::wrap(activate)),
+        })
+    }
+
+    /// Registers a link matcher for `pattern` that calls `handler` with the
+    /// mouse event and the matched text when a link is activated.
+    ///
+    /// `pattern` is compiled to a JS `RegExp` (Rust style named groups are
+    /// accepted). The link provider API ([`register_link_detector`],
+    /// [`RegexDetector`]) should be preferred; xterm.js has deprecated link
+    /// matchers.
+    ///
+    /// Dropping the returned [`LinkMatcher`] deregisters the matcher and frees
+    /// `handler` and the callbacks in `options`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the `SyntaxError` thrown by `RegExp` if `pattern` is not a
+    /// valid regular expression.
+    ///
+    /// [`register_link_detector`]: Terminal::register_link_detector
+    pub fn register_regex_link_matcher<F>(
+        &self,
+        pattern: &str,
+        mut handler: F,
+        options: RegexLinkMatcherOptions,
+    ) -> Result<LinkMatcher, JsValue>
+    where
+        F: FnMut(&MouseEvent, &str) + 'static,
+    {
+        let regex = compile_regex(pattern, "")?;
+        let handler: Box<dyn FnMut(MouseEvent, Str)> =
+            Box::new(move |event, uri| handler(&event, &uri));
+        let handler = Closure::wrap(handler);
+
+        let RegexLinkMatcherOptions {
+            match_index,
+            priority,
+            validation,
+            tooltip,
+            leave,
+            will_link_activate,
+        } = options;
+
+        let js_options = Object::new();
+        let mut callbacks: Vec<Box<dyn Debug>> = Vec::new();
+        let set = |key: &str, value: &JsValue| {
+            let _ = Reflect::set(&js_options, &JsValue::from_str(key), value);
+        };
+
+        if let Some(match_index) = match_index {
+            set("matchIndex", &match_index.into());
+        }
+        if let Some(priority) = priority {
+            set("priority", &priority.into());
+        }
+
+        if let Some(mut validate) = validation {
+            let validate: Box<dyn FnMut(Str, Function)> =
+                Box::new(move |uri, callback| {
+                    let valid = JsValue::from_bool(validate(&uri));
+                    let _ = callback.call1(&JsValue::NULL, &valid);
+                });
+            let validate = Closure::wrap(validate);
+            set("validationCallback", validate.as_ref());
+            callbacks.push(Box::new(validate));
+        }
+        if let Some(mut on_tooltip) = tooltip {
+            let on_tooltip: Box<dyn FnMut(MouseEvent, Str, JsValue) -> bool> =
+                Box::new(move |event, uri, range| {
+                    on_tooltip(&event, &uri, &read_viewport_range(&range))
+                });
+            let on_tooltip = Closure::wrap(on_tooltip);
+            set("tooltipCallback", on_tooltip.as_ref());
+            callbacks.push(Box::new(on_tooltip));
+        }
+        if let Some(on_leave) = leave {
+            let on_leave = Closure::wrap(on_leave);
+            set("leaveCallback", on_leave.as_ref());
+            callbacks.push(Box::new(on_leave));
+        }
+        if let Some(mut will_link_activate) = will_link_activate {
+            let will_link_activate: Box<dyn FnMut(MouseEvent, Str) -> bool> =
+                Box::new(move |event, uri| will_link_activate(&event, &uri));
+            let will_link_activate = Closure::wrap(will_link_activate);
+            set("willLinkActivate", will_link_activate.as_ref());
+            callbacks.push(Box::new(will_link_activate));
+        }
+
+        Ok(LinkMatcher {
+            id: self.register_link_matcher_with_object(
+                &regex,
+                &handler,
+                &js_options,
+            ),
+            terminal: self.clone(),
+            handler,
+            callbacks,
+        })
+    }
+}
@@ -1,9 +1,18 @@
-//! Extra functions for [Marker]s.
+//! Extra functions for [Marker]s and a [range tracker](TrackedRange) built on
+//! them.
 //!
 //! [Marker]: crate::xterm::Marker
 
+use super::{Disposable, DisposableWrapper};
 use crate::idx_to_opt;
-use crate::xterm::Marker;
+use crate::xterm::{Marker, Terminal};
+
+use wasm_bindgen::prelude::*;
+
+use core::cell::{Cell, RefCell};
+use core::fmt::{self, Debug};
+use core::ops::RangeInclusive;
+use std::rc::Rc;
 
 impl Marker {
     /// The actual line index in the buffer at this point in time.
@@ -16,3 +25,174 @@ impl Marker {
         idx_to_opt(self.line())
     }
 }
+
+/// State shared between a [`TrackedRange`] and its dispose listeners.
+#[derive(Default)]
+struct Invalidation {
+    /// Whether either marker has been disposed.
+    invalidated: Cell<bool>,
+    /// Called (once) when the range is invalidated.
+    handler: RefCell<Option<Box<dyn FnOnce()>>>,
+}
+
+impl Invalidation {
+    /// Marks the range as invalidated, running the handler if this is the
+    /// first time.
+    fn invalidate(&self) {
+        if !self.invalidated.replace(true) {
+            let handler = self.handler.borrow_mut().take();
+            if let Some(handler) = handler {
+                handler();
+            }
+        }
+    }
+}
+
+/// A range of lines in the normal buffer, anchored to the content on those
+/// lines with a pair of [`Marker`]s.
+///
+/// As lines are added and scrollback is trimmed the range moves with the
+/// content it was created for. Once the start or end line is trimmed from the
+/// scrollback (or either marker is disposed some other way) the range is
+/// _invalidated_: it no longer reports lines and the handler given to
+/// [`on_invalidate`] is called.
+///
+/// Dropping a [`TrackedRange`] disposes of both markers (without running the
+/// invalidation handler).
+///
+/// [`on_invalidate`]: TrackedRange::on_invalidate
+pub struct TrackedRange {
+    /// Marks the first line of the range.
+    start: Marker,
+    /// Marks the last line of the range.
+    end: Marker,
+    /// Whether the range is still valid, and what to do when it isn't.
+    state: Rc<Invalidation>,
+    /// The dispose listeners on the two markers; these are dropped (and the
+    /// listeners removed) before the markers are disposed.
+    listeners: Option<[DisposableWrapper<Disposable>; 2]>,
+    /// The closure behind the dispose listeners.
+    listener: Closure<dyn FnMut()>,
+}
+
+impl Debug for TrackedRange {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("TrackedRange")
+            .field("start", &self.start)
+            .field("end", &self.end)
+            .field("invalidated", &self.state.invalidated.get())
+            .field("listeners", &self.listeners)
+            .field("listener", &self.listener)
+            .finish()
+    }
+}
+
+impl TrackedRange {
+    /// Tracks the lines from `start` to `end` (inclusive).
+    ///
+    /// If either marker is already disposed the range starts out invalidated.
+    #[must_use]
+    pub fn new(start: Marker, end: Marker) -> Self {
+        let state = Rc::new(Invalidation::default());
+
+        let listener: Box<dyn FnMut()> = {
+            let state = state.clone();
+            Box::new(move || state.invalidate())
+        };
+        let listener = Closure::wrap(listener);
+
+        let listeners = [
+            start.on_dispose(&listener).into(),
+            end.on_dispose(&listener).into(),
+        ];
+
+        if start.is_disposed() || end.is_disposed() {
+            state.invalidate();
+        }
+
+        Self {
+            start,
+            end,
+            state,
+            listeners: Some(listeners),
+            listener,
+        }
+    }
+
+    /// Sets the function to call when the range is invalidated, replacing any
+    /// existing handler.
+    ///
+    /// If the range has already been invalidated, `handler` is called
+    /// immediately.
+    pub fn on_invalidate<F: FnOnce() + 'static>(&self, handler: F) {
+        if self.state.invalidated.get() {
+            handler();
+        } else {
+            *self.state.handler.borrow_mut() = Some(Box::new(handler));
+        }
+    }
+
+    /// Whether the range is still being tracked.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        !self.state.invalidated.get()
+    }
+
+    /// The lines (buffer line indexes) the range currently spans, or `None`
+    /// if the range has been invalidated.
+    #[must_use]
+    pub fn lines(&self) -> Option<RangeInclusive<u32>> {
+        if !self.is_valid() {
+            return None;
+        }
+
+        let (start, end) = (self.start.get_line()?, self.end.get_line()?);
+        Some(start.min(end)..=start.max(end))
+    }
+
+    /// The marker on the first line of the range.
+    #[must_use]
+    pub fn start(&self) -> &Marker {
+        &self.start
+    }
+
+    /// The marker on the last line of the range.
+    #[must_use]
+    pub fn end(&self) -> &Marker {
+        &self.end
+    }
+}
+
+impl Drop for TrackedRange {
+    fn drop(&mut self) {
+        drop(self.listeners.take());
+
+        self.start.dispose();
+        self.end.dispose();
+    }
+}
+
+impl Terminal {
+    /// Registers a pair of markers and returns a [`TrackedRange`] spanning
+    /// them.
+    ///
+    /// The offsets are relative to the cursor's line; i.e. `track_range(-2,
+    /// 0)` tracks the two lines above the cursor and the cursor's line.
+    ///
+    /// Returns `None` if the markers can't be registered (i.e. when the alt
+    /// buffer is active).
+    #[must_use]
+    pub fn track_range(
+        &self,
+        start_offset: i32,
+        end_offset: i32,
+    ) -> Option<TrackedRange> {
+        let start = self.register_marker(start_offset)?;
+        if let Some(end) = self.register_marker(end_offset) {
+            Some(TrackedRange::new(start, end))
+        } else {
+            start.dispose();
+            None
+        }
+    }
+}
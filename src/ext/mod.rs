@@ -2,8 +2,17 @@
 
 use super::xterm::{Disposable, Terminal, TerminalAddon, TerminalOptions};
 
+use js_sys::{Function, Reflect};
 use wasm_bindgen::convert::{FromWasmAbi, IntoWasmAbi};
-use wasm_bindgen::JsCast;
+use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
+
+use core::any::Any;
+use core::cell::RefCell;
+use core::fmt::{self, Debug};
+use core::future::Future;
+use core::ops::Deref;
+use core::pin::Pin;
+use std::rc::Rc;
 
 // The `object` macro and the `IntoJsInterface` trait are good candidates for
 // being spun off into their own crate, along with macros that generate some of
@@ -13,15 +22,48 @@ use wasm_bindgen::JsCast;
 /// here so we don't have to make any assumptions about the call-site.
 #[doc(hidden)]
 pub mod _m_sprt {
+    pub use core::any::Any;
+    pub use core::cell::RefCell;
     pub use core::clone::Clone;
     pub use core::convert::AsRef;
     pub use core::marker::Sized;
-    pub use core::{concat, stringify};
+    pub use core::option::Option;
+    pub use core::{compile_error, concat, stringify};
     pub use std::boxed::Box;
+    pub use std::rc::Rc;
+    pub use std::vec::Vec;
 
-    pub use js_sys::{Object, Reflect};
+    pub use js_sys::{Object, Promise, Reflect};
     pub use wasm_bindgen::prelude::Closure;
     pub use wasm_bindgen::{JsCast, JsValue};
+    pub use wasm_bindgen_futures::{future_to_promise, JsFuture};
+
+    use core::cell::{Ref, RefMut};
+
+    /// Borrows the Rust value behind an interface's JS object.
+    ///
+    /// Throws a JS error if the value is mutably borrowed, which happens when
+    /// a `&mut self` method calls (through JS) back into the same object.
+    pub fn borrow<T>(value: &RefCell<T>) -> Ref<'_, T> {
+        value.try_borrow().unwrap_or_else(|_| {
+            wasm_bindgen::throw_str(
+                "a Rust interface implementation was re-entered while \
+                it was mutably borrowed",
+            )
+        })
+    }
+
+    /// Mutably borrows the Rust value behind an interface's JS object.
+    ///
+    /// Throws a JS error if the value is already borrowed (see [`borrow`]).
+    pub fn borrow_mut<T>(value: &RefCell<T>) -> RefMut<'_, T> {
+        value.try_borrow_mut().unwrap_or_else(|_| {
+            wasm_bindgen::throw_str(
+                "a Rust interface implementation was re-entered while \
+                it was borrowed",
+            )
+        })
+    }
 }
 
 /// Uses the workaround detailed [here] to let us 'generate' a doc literal.
@@ -44,7 +86,7 @@ macro_rules! calculated_doc {
 ///
 /// In addition to the actual trait, this produces:
 ///   - glue that lets `'static` instances of the Rust trait be "turned into"
-///     instances of the JS interface
+///     instances of the JS interface (see [`InterfaceHandle`])
 ///   - a blanket impl that implements the Rust trait for all `wasm-bindgen`
 ///     produced types that extend the JS interface
 ///   - an implementation of [`IntoJsInterface`] for all things that implement
@@ -52,6 +94,38 @@ macro_rules! calculated_doc {
 ///     implementations of the Rust or implementations of the JS interface
 ///     with `impl IntoJsInterface<JsInterfaceName>`
 ///
+/// The trait's members are methods (`fn name(&self, ..) -> T;`) and properties;
+/// properties are written as `get fn name(&self) -> T;` or, equivalently, as
+/// `readonly name: T;`. Properties are put on the JS object as getters (i.e.
+/// with `Object.defineProperty`) so JS code that reads `obj.name` calls the
+/// Rust method; on the Rust side they're methods either way.
+///
+/// Members can also be `async fn name(&self, ..) -> T;` for JS methods that
+/// return a `Promise`. The trait method returns a [`LocalBoxFuture<T>`] (so it
+/// can't borrow from `self`) and `T` must be a [`PromiseOutput`], i.e.
+/// `Result<JsValue, JsValue>`. Rust implementations are turned into JS methods
+/// that return a `Promise` (see [`future_to_promise`]) and JS implementations
+/// have their `Promise`s turned into futures (see [`JsFuture`]).
+///
+/// Methods can have a default body (i.e. `fn dispose(&self) {}`), which is
+/// handy for members that traits extending this one inherit.
+///
+/// Methods can take `&mut self` too. The Rust value is kept in a `RefCell`,
+/// so a `&mut self` method that (through JS) calls back into the same object
+/// throws a JS exception instead of aliasing.
+///
+/// The [`xterm_interface`] attribute macro (behind the `macros` feature) is an
+/// alternative to this macro that uses regular trait syntax.
+///
+/// The trait also gets a `ts_declaration` function that produces TypeScript
+/// typings for the JS side of the interface; see the [`typescript`] module.
+///
+/// Methods marked `#[optional]` (after their doc comments but before any other
+/// attributes) are for members that JS objects don't have to have. These
+/// become associated consts holding an `Option<fn(&Self, ..)>` that's `None`
+/// unless an implementor sets it; the member is only put on the JS object when
+/// it's `Some`.
+///
 /// Note: if you get an error about "unconditional recursion" when using this
 /// macro or an error about a trait not being in scope, it's because a method
 /// that you added to the trait doesn't exist on the underlying JS interface.
@@ -74,9 +148,574 @@ macro_rules! calculated_doc {
 /// probably renders the little utility the `IntoJsInterface` trait had moot.
 ///
 /// [`IntoJsInterface`]: crate::ext::IntoJsInterface
+/// [`InterfaceHandle`]: crate::ext::InterfaceHandle
+/// [`LocalBoxFuture<T>`]: crate::ext::LocalBoxFuture
+/// [`PromiseOutput`]: crate::ext::PromiseOutput
+/// [`future_to_promise`]: wasm_bindgen_futures::future_to_promise
+/// [`JsFuture`]: wasm_bindgen_futures::JsFuture
 /// [`JsCast::dyn_into`]: wasm_bindgen::JsCast::dyn_into
+/// [`typescript`]: crate::ext::typescript
+/// [`xterm_interface`]: https://docs.rs/xterm-js-sys/latest/xterm_js_sys/ext/attr.xterm_interface.html
 #[macro_export]
 macro_rules! interface {
+    // Normalizes the members of the interface, one at a time, into:
+    // `{ [kind] [attrs] [mut] [self] name (args) [return type] [body] }`.
+    //
+    // The receiver's `self` is kept (rather than written out by this macro)
+    // so that default bodies can use it.
+    (@munch [$($head:tt)*] $members:tt) => {
+        $crate::interface! { @generate $($head)* $members }
+    };
+    (@munch $head:tt [$($acc:tt)*]
+        $(#[$m:meta])*
+        async fn $name:ident (& $self_:tt $(, $arg:ident: $ty:ty)* $(,)?)
+            -> $ret:ty;
+        $($rest:tt)*
+    ) => {
+        $crate::interface! { @munch $head [$($acc)*
+            {
+                [promise $ret] [$(#[$m])*] [] [$self_] $name ($($arg: $ty),*)
+                [$crate::ext::LocalBoxFuture<$ret>] []
+            }
+        ] $($rest)* }
+    };
+    (@munch $head:tt [$($acc:tt)*]
+        $(#[$m:meta])*
+        get fn $name:ident (& $self_:tt $(,)?) -> $ret:ty;
+        $($rest:tt)*
+    ) => {
+        $crate::interface! { @munch $head [$($acc)*
+            { [getter] [$(#[$m])*] [] [$self_] $name () [$ret] [] }
+        ] $($rest)* }
+    };
+    (@munch $head:tt [$($acc:tt)*]
+        $(#[$m:meta])*
+        readonly $name:ident: $ty:ty;
+        $($rest:tt)*
+    ) => {
+        $crate::interface! { @munch $head [$($acc)*
+            { [getter] [$(#[$m])*] [] [self] $name () [$ty] [] }
+        ] $($rest)* }
+    };
+    (@munch $head:tt [$($acc:tt)*]
+        $(#[doc = $doc:expr])*
+        #[optional]
+        $(#[$m:meta])*
+        fn $name:ident (& $self_:tt $(, $arg:ident: $ty:ty)* $(,)?)
+            $(-> $ret:ty)?
+            ;
+        $($rest:tt)*
+    ) => {
+        $crate::interface! { @munch $head [$($acc)*
+            {
+                [optional] [$(#[doc = $doc])* $(#[$m])*] [] [$self_]
+                $name ($($arg: $ty),*) [$($ret)?] []
+            }
+        ] $($rest)* }
+    };
+    (@munch $head:tt [$($acc:tt)*]
+        $(#[$m:meta])*
+        // Methods take `&self` or `&mut self`; the Rust value is kept in a
+        // `RefCell` so that the latter is possible.
+        fn $name:ident (&mut $self_:tt $(, $arg:ident: $ty:ty)* $(,)?)
+            $(-> $ret:ty)?
+            ;
+        $($rest:tt)*
+    ) => {
+        $crate::interface! { @munch $head [$($acc)*
+            {
+                [method] [$(#[$m])*] [mut] [$self_] $name ($($arg: $ty),*)
+                [$($ret)?] []
+            }
+        ] $($rest)* }
+    };
+    (@munch $head:tt [$($acc:tt)*]
+        $(#[$m:meta])*
+        fn $name:ident (&mut $self_:tt $(, $arg:ident: $ty:ty)* $(,)?)
+            $(-> $ret:ty)?
+            $body:block
+        $($rest:tt)*
+    ) => {
+        $crate::interface! { @munch $head [$($acc)*
+            {
+                [method] [$(#[$m])*] [mut] [$self_] $name ($($arg: $ty),*)
+                [$($ret)?] [$body]
+            }
+        ] $($rest)* }
+    };
+    (@munch $head:tt [$($acc:tt)*]
+        $(#[$m:meta])*
+        fn $name:ident (& $self_:tt $(, $arg:ident: $ty:ty)* $(,)?)
+            $(-> $ret:ty)?
+            ;
+
+        // This is intentionally very constrained. The idea is that this
+        // just mirrors the JS interface. if you want to offer additional
+        // functionality on your Rust trait, use an extension trait.
+        $($rest:tt)*
+    ) => {
+        $crate::interface! { @munch $head [$($acc)*
+            {
+                [method] [$(#[$m])*] [] [$self_] $name ($($arg: $ty),*)
+                [$($ret)?] []
+            }
+        ] $($rest)* }
+    };
+    (@munch $head:tt [$($acc:tt)*]
+        $(#[$m:meta])*
+        fn $name:ident (& $self_:tt $(, $arg:ident: $ty:ty)* $(,)?)
+            $(-> $ret:ty)?
+            $body:block
+        $($rest:tt)*
+    ) => {
+        $crate::interface! { @munch $head [$($acc)*
+            {
+                [method] [$(#[$m])*] [] [$self_] $name ($($arg: $ty),*)
+                [$($ret)?] [$body]
+            }
+        ] $($rest)* }
+    };
+    (@munch $head:tt $acc:tt $($rest:tt)+) => {
+        $crate::ext::_m_sprt::compile_error!(
+            "interface members must be `fn name(&self, ..);` (or `&mut \
+            self`, with an optional default body, or `#[optional]`), \
+            `async fn name(&self, ..) -> T;`, `get fn name(&self) -> T;` \
+            or `readonly name: T;`"
+        );
+    };
+
+    // The trait's version of a member.
+    (@trait_item [optional] [$(#[$m:meta])*] [] [$self_:tt] $js:ident
+        $name:ident ($($arg:ident: $ty:ty),*) [$($ret:ty)?] []
+    ) => {
+        $crate::calculated_doc! {
+            #[doc = "\n"]
+            #[doc = $crate::ext::_m_sprt::concat!(
+                " Dual of ",
+                "[`",
+                    $crate::ext::_m_sprt::stringify!($js),
+                    "::",
+                    $crate::ext::_m_sprt::stringify!($name),
+                "`].",
+            )]
+            #[doc = "\n"]
+            #[doc = " This member is optional: it's only put on the JS object"]
+            #[doc = " when this is `Some`."]
+            >>>
+            #[allow(non_upper_case_globals)]
+            const $name: $crate::ext::_m_sprt::Option<
+                fn(&Self $(, $ty)*) $(-> $ret)?
+            > = $crate::ext::_m_sprt::Option::None;
+            $(#[$m])*
+        }
+    };
+    (@trait_item [$($kind:tt)*] [$(#[$m:meta])*]
+        [$($mut:tt)?] [$self_:tt] $js:ident
+        $name:ident ($($arg:ident: $ty:ty),*) [$($ret:ty)?] [$body:block]
+    ) => {
+        $crate::calculated_doc! {
+            #[doc = "\n"]
+            #[doc = $crate::ext::_m_sprt::concat!(
+                " Dual of ",
+                "[`",
+                    $crate::ext::_m_sprt::stringify!($js),
+                    "::",
+                    $crate::ext::_m_sprt::stringify!($name),
+                "`].",
+            )]
+            >>>
+            fn $name(
+                & $($mut)? $self_,
+                $(
+                    $arg: $ty,
+                )*
+            ) $(-> $ret)? $body
+            $(#[$m])*
+        }
+    };
+    (@trait_item [$($kind:tt)*] [$(#[$m:meta])*]
+        [$($mut:tt)?] [$self_:tt] $js:ident
+        $name:ident ($($arg:ident: $ty:ty),*) [$($ret:ty)?] []
+    ) => {
+        $crate::calculated_doc! {
+            #[doc = "\n"]
+            #[doc = $crate::ext::_m_sprt::concat!(
+                " Dual of ",
+                "[`",
+                    $crate::ext::_m_sprt::stringify!($js),
+                    "::",
+                    $crate::ext::_m_sprt::stringify!($name),
+                "`].",
+            )]
+            >>>
+            fn $name(
+                & $($mut)? $self_,
+                $(
+                    $arg: $ty,
+                )*
+            ) $(-> $ret)?;
+            $(#[$m])*
+        }
+    };
+
+    // The version of a member for JS implementations of the interface.
+    (@js_item [optional] [] $js:ident $nom:ident
+        $name:ident ($($arg:ident: $ty:ty),*) [$($ret:ty)?]
+    ) => {
+        $crate::calculated_doc! {
+            #[doc = $crate::ext::_m_sprt::concat!(
+                " [`",
+                    $crate::ext::_m_sprt::stringify!($name),
+                "`](",
+                    $crate::ext::_m_sprt::stringify!($nom),
+                    "::",
+                    $crate::ext::_m_sprt::stringify!($name),
+                ")",
+                " for types that implement the ",
+                "[`",
+                    $crate::ext::_m_sprt::stringify!($js),
+                "`]",
+                " interface.\n",
+            )]
+            #[doc = "\n"]
+            #[doc = " This is always `Some`; calling it throws if the JS"]
+            #[doc = " object doesn't have the member."]
+            >>>
+            #[allow(non_upper_case_globals)]
+            const $name: $crate::ext::_m_sprt::Option<
+                fn(&Self $(, $ty)*) $(-> $ret)?
+            > = $crate::ext::_m_sprt::Option::Some(
+                |this: &Self $(, $arg: $ty)*| $js::$name(
+                    $crate::ext::_m_sprt::AsRef::<$js>::as_ref(this),
+                    $($arg,)*
+                )
+            );
+        }
+    };
+    (@js_item [$($kind:tt)*] [$($mut:tt)?] $js:ident $nom:ident
+        $name:ident ($($arg:ident: $ty:ty),*) [$($ret:ty)?]
+    ) => {
+        $crate::calculated_doc! {
+            #[doc = $crate::ext::_m_sprt::concat!(
+                " [`",
+                    $crate::ext::_m_sprt::stringify!($name),
+                "`](",
+                    $crate::ext::_m_sprt::stringify!($nom),
+                    "::",
+                    $crate::ext::_m_sprt::stringify!($name),
+                ")",
+                " for types that implement the ",
+                "[`",
+                    $crate::ext::_m_sprt::stringify!($js),
+                "`]",
+                " interface.",
+            )]
+            >>>
+            fn $name(
+                & $($mut)? self
+                $(, $arg: $ty)*
+            ) $(-> $ret)? {
+                let this: &Self = self;
+
+                $crate::interface! { @forward [$($kind)*]
+                    $js::$name(
+                        $crate::ext::_m_sprt::AsRef
+                            ::<$js>::as_ref(this),
+                        $($arg,)*
+                    )
+                }
+            }
+
+            // Unfortunately there doesn't seem to be syntax that
+            // lets us say that, when there's ambiguity between
+            // a trait method and an inherent method, use the
+            // inherent method (we can, however, say to use the
+            // trait method using FQS).
+            //
+            // That's a problem here because it means that we
+            // fall back on the trait method that we're implementing
+            // when the underlying inherent method we want to proxy
+            // doesn't exist.
+            //
+            // We can still make this a hard error by forbidding
+            // obviously infinitely recursion functions, but this
+            // isn't perfect since it produces a pretty cryptic
+            // error (we'd like to say that the method doesn't
+            // exist on the JS interface). Alas, without a proc
+            // macro or without naming the proxied methods something
+            // different (I think this'd also need a proc macro or
+            // the paste crate) this is probably as good as we can
+            // do.
+            //
+            // This is mentioned in the macro's docs.
+            //
+            // Actually, one way we can do this kind of check is
+            // to make a fake module (that's hidden) and to, within
+            // it, not import the Rust trait but to import the JS
+            // interface and then "use" the functions on the
+            // interface we're after (not directly unfortunately —
+            // structs don't work like that in Rust). We can use
+            // them by doing something like:
+            // ```rust
+            // mod __testing {
+            //   use super::$js;
+            //   const $name: () = {
+            //     let _ = $js::$name; ()
+            //   };
+            // }
+            // ```
+            // Unfortunately the `super::$js` part won't
+            // work if $js is an absolute path and the
+            // error message will (not so helpfully in this case)
+            // suggest that we import the Rust trait into scope to
+            // try to fix the problem which is possibly more
+            // confusing in this case.
+            //
+            // So, I think we'll just leave it for now.
+            //
+            // Actually we can get around the above by having the
+            // module above `use` $js `as` something else
+            // within a scope. So, let's do it!
+            //
+            // Okay! Done!
+            //
+            // Note that if you do things like have mismatched
+            // parameters you might get the recursion error instead
+            // (I _think_ if the inherent method is there it'll
+            // resolve to it over the trait method, but I'm not
+            // 100% sure).
+            //
+            // It's possible to expand the module hack above to
+            // check for this too (you'd do something like:
+            // ```rust
+            // #[doc(hidden)]
+            // fn $name($($arg: arg_ty),*) $(-> $ret)? {
+            //   Js::$name($($arg: arg_ty),*)
+            // }
+            // ```
+            // which should error on any type/arity mismatches); the
+            // `@check` arms do something along those lines.
+            //
+            // Pretty sure about the inherent method thing and even
+            // if that isn't true, we've still got the unconditional
+            // recursion error.
+            //
+            #[forbid(unconditional_recursion)]
+        }
+    };
+
+    // Describes a member for the TypeScript declaration.
+    (@ts_member [promise $out:ty] $name:ident
+        ($($arg:ident: $ty:ty),*) [$($ret:ty)?]
+    ) => {
+        $crate::interface! {
+            @ts_member_of Promise $name ($($arg: $ty),*) [$out]
+        }
+    };
+    (@ts_member [method] $name:ident
+        ($($arg:ident: $ty:ty),*) [$($ret:ty)?]
+    ) => {
+        $crate::interface! {
+            @ts_member_of Method $name ($($arg: $ty),*) [$($ret)?]
+        }
+    };
+    (@ts_member [getter] $name:ident
+        ($($arg:ident: $ty:ty),*) [$($ret:ty)?]
+    ) => {
+        $crate::interface! {
+            @ts_member_of Getter $name ($($arg: $ty),*) [$($ret)?]
+        }
+    };
+    (@ts_member [optional] $name:ident
+        ($($arg:ident: $ty:ty),*) [$($ret:ty)?]
+    ) => {
+        $crate::interface! {
+            @ts_member_of Optional $name ($($arg: $ty),*) [$($ret)?]
+        }
+    };
+    (@ts_member_of $kind:ident $name:ident
+        ($($arg:ident: $ty:ty),*) [$($ret:ty)?]
+    ) => {
+        $crate::ext::TsMember::new(
+            $crate::ext::TsMemberKind::$kind,
+            $crate::ext::_m_sprt::stringify!($name),
+            &[$((
+                $crate::ext::_m_sprt::stringify!($arg),
+                $crate::ext::_m_sprt::stringify!($ty),
+            )),*],
+            $crate::ext::_m_sprt::concat!(
+                "" $(, $crate::ext::_m_sprt::stringify!($ret))?
+            ),
+        )
+    };
+
+    // Makes the closure for a member and puts it on the JS object.
+    (@install [method] [mut] $this:ident $obj:ident $resources:ident
+        $name:ident ($($arg:ident: $ty:ty),*) [$($ret:ty)?]
+    ) => {
+        let closure: $crate::ext::_m_sprt::Closure<dyn FnMut(
+            $($ty,)*
+        ) $(-> $ret)?> = {
+            let this = $crate::ext::_m_sprt::Rc::clone($this);
+            $crate::ext::_m_sprt::Closure::wrap(
+                $crate::ext::_m_sprt::Box::new(move |$($arg: $ty, )*| {
+                    let mut this = $crate::ext::_m_sprt::borrow_mut(&this);
+                    Self::$name(&mut *this $(, $arg)*)
+                })
+            )
+        };
+
+        let _ = $crate::ext::_m_sprt::Reflect::set(
+            $obj,
+            &$crate::ext::_m_sprt::JsValue::from_str(
+                $crate::ext::_m_sprt::stringify!($name)
+            ),
+            closure.as_ref(),
+        ).unwrap();
+        $resources.push($crate::ext::_m_sprt::Box::new(closure));
+    };
+    (@install [method] [] $this:ident $obj:ident $resources:ident
+        $name:ident ($($arg:ident: $ty:ty),*) [$($ret:ty)?]
+    ) => {
+        let closure: $crate::ext::_m_sprt::Closure<dyn FnMut(
+            $($ty,)*
+        ) $(-> $ret)?> = {
+            let this = $crate::ext::_m_sprt::Rc::clone($this);
+            $crate::ext::_m_sprt::Closure::wrap(
+                $crate::ext::_m_sprt::Box::new(move |$($arg: $ty, )*| {
+                    Self::$name(
+                        &*$crate::ext::_m_sprt::borrow(&this)
+                        $(, $arg)*
+                    )
+                })
+            )
+        };
+
+        let _ = $crate::ext::_m_sprt::Reflect::set(
+            $obj,
+            &$crate::ext::_m_sprt::JsValue::from_str(
+                $crate::ext::_m_sprt::stringify!($name)
+            ),
+            closure.as_ref(),
+        ).unwrap();
+        $resources.push($crate::ext::_m_sprt::Box::new(closure));
+    };
+    (@install [optional] [] $this:ident $obj:ident $resources:ident
+        $name:ident ($($arg:ident: $ty:ty),*) [$($ret:ty)?]
+    ) => {
+        if let $crate::ext::_m_sprt::Option::Some(member) = Self::$name {
+            let closure: $crate::ext::_m_sprt::Closure<dyn FnMut(
+                $($ty,)*
+            ) $(-> $ret)?> = {
+                let this = $crate::ext::_m_sprt::Rc::clone($this);
+                $crate::ext::_m_sprt::Closure::wrap(
+                    $crate::ext::_m_sprt::Box::new(move |$($arg: $ty, )*| {
+                        member(
+                            &*$crate::ext::_m_sprt::borrow(&this)
+                            $(, $arg)*
+                        )
+                    })
+                )
+            };
+
+            let _ = $crate::ext::_m_sprt::Reflect::set(
+                $obj,
+                &$crate::ext::_m_sprt::JsValue::from_str(
+                    $crate::ext::_m_sprt::stringify!($name)
+                ),
+                closure.as_ref(),
+            ).unwrap();
+            $resources.push($crate::ext::_m_sprt::Box::new(closure));
+        }
+    };
+    (@install [getter] [] $this:ident $obj:ident $resources:ident
+        $name:ident () [$ret:ty]
+    ) => {
+        let closure: $crate::ext::_m_sprt::Closure<dyn FnMut() -> $ret> = {
+            let this = $crate::ext::_m_sprt::Rc::clone($this);
+            $crate::ext::_m_sprt::Closure::wrap(
+                $crate::ext::_m_sprt::Box::new(move || {
+                    Self::$name(&*$crate::ext::_m_sprt::borrow(&this))
+                })
+            )
+        };
+
+        let _ = $crate::ext::_m_sprt::Object::define_property(
+            $obj,
+            &$crate::ext::_m_sprt::JsValue::from_str(
+                $crate::ext::_m_sprt::stringify!($name)
+            ),
+            &$crate::ext::object! {
+                get: closure,
+                enumerable: $crate::ext::_m_sprt::JsValue::from(true),
+                configurable: $crate::ext::_m_sprt::JsValue::from(true),
+            },
+        );
+        $resources.push($crate::ext::_m_sprt::Box::new(closure));
+    };
+    (@install [promise $out:ty] [] $this:ident $obj:ident $resources:ident
+        $name:ident ($($arg:ident: $ty:ty),*) [$ret:ty]
+    ) => {
+        let closure: $crate::ext::_m_sprt::Closure<dyn FnMut(
+            $($ty,)*
+        ) -> $crate::ext::_m_sprt::Promise> = {
+            let this = $crate::ext::_m_sprt::Rc::clone($this);
+            $crate::ext::_m_sprt::Closure::wrap(
+                $crate::ext::_m_sprt::Box::new(move |$($arg: $ty, )*| {
+                    let future = Self::$name(
+                        &*$crate::ext::_m_sprt::borrow(&this)
+                        $(, $arg)*
+                    );
+
+                    $crate::ext::_m_sprt::future_to_promise(async move {
+                        $crate::ext::PromiseOutput::into_settled(future.await)
+                    })
+                })
+            )
+        };
+
+        let _ = $crate::ext::_m_sprt::Reflect::set(
+            $obj,
+            &$crate::ext::_m_sprt::JsValue::from_str(
+                $crate::ext::_m_sprt::stringify!($name)
+            ),
+            closure.as_ref(),
+        ).unwrap();
+        $resources.push($crate::ext::_m_sprt::Box::new(closure));
+    };
+
+    // Checks that a member exists on the JS interface and that its signature
+    // matches (for async members, that the JS method returns a `Promise`).
+    (@check [promise $out:ty] $js:ident $name:ident ($($ty:ty),*) [$ret:ty]) => {
+        #[allow(non_upper_case_globals, dead_code)]
+        const $name: () = {
+            let _: fn(&$js $(, $ty)*) -> $crate::ext::_m_sprt::Promise =
+                $js::$name;
+            ()
+        };
+    };
+    (@check [$($kind:tt)*] $js:ident $name:ident ($($ty:ty),*) [$($ret:ty)?]) => {
+        #[allow(non_upper_case_globals, dead_code)]
+        const $name: () = {
+            let _: fn(&$js $(, $ty)*) $(-> $ret)? = $js::$name;
+            ()
+        };
+    };
+
+    // Calls a member of a JS implementation of the interface.
+    (@forward [promise $out:ty] $call:expr) => {{
+        let promise: $crate::ext::_m_sprt::Promise = $call;
+
+        $crate::ext::_m_sprt::Box::pin(async move {
+            <$out as $crate::ext::PromiseOutput>::from_settled(
+                $crate::ext::_m_sprt::JsFuture::from(promise).await
+            )
+        })
+    }};
+    (@forward [$($kind:tt)*] $call:expr) => {
+        $call
+    };
+
     (
         $(#[$metas:meta])*
         $vis:vis trait $nom:ident
@@ -85,20 +724,30 @@ macro_rules! interface {
                 $(Self extends $ext_js:path as $ext_rs:path,)+
             )?
     {
-        $(
-            $(#[$fn_metas:meta])*
-            // All functions that we can mirror need to take `&self` so this is
-            // okay.
-            fn $fn_name:ident (&self $(, $arg_name:ident: $arg_ty:ty)* $(,)?)
-                $(-> $ret_ty:ty)?
-                ;
-            // Default impls are not supported for now.
-
-            // This is intentionally very constrained. The idea is that this
-            // just mirrors the JS interface. if you want to offer additional
-            // functionality on your Rust trait, use an extension trait.
-        )*
+        $($members:tt)*
     }) => {
+        $crate::interface! { @munch
+            [
+                [$(#[$metas])*] [$vis] [$nom] [$js_interface]
+                [$($($ext_js as $ext_rs),+)?]
+            ]
+            []
+            $($members)*
+        }
+    };
+
+    (@generate
+        [$(#[$metas:meta])*] [$vis:vis] [$nom:ident] [$js_interface:ident]
+        [$($($ext_js:path as $ext_rs:path),+)?]
+        [$({
+            [$($kind:tt)*]
+            [$(#[$fn_metas:meta])*]
+            [$($recv_mut:tt)?] [$recv_self:tt]
+            $fn_name:ident ($($arg_name:ident: $arg_ty:ty),*)
+            [$($ret_ty:ty)?]
+            [$($body:block)?]
+        })*]
+    ) => {
         $crate::calculated_doc! {
             #[doc = $crate::ext::_m_sprt::concat!(
                 " Rust version of the ",
@@ -123,24 +772,11 @@ macro_rules! interface {
             )?
             {
                 $(
-                    $crate::calculated_doc! {
-                        #[doc = "\n"]
-                        #[doc = $crate::ext::_m_sprt::concat!(
-                            " Dual of ",
-                            "[`",
-                                $crate::ext::_m_sprt::stringify!($js_interface),
-                                "::",
-                                $crate::ext::_m_sprt::stringify!($fn_name),
-                            "`].",
-                        )]
-                        >>>
-                        fn $fn_name(
-                            &self,
-                            $(
-                                $arg_name: $arg_ty,
-                            )*
-                        ) $(-> $ret_ty)?;
-                        $(#[$fn_metas])*
+                    $crate::interface! {
+                        @trait_item [$($kind)*] [$(#[$fn_metas])*]
+                            [$($recv_mut)?] [$recv_self] $js_interface
+                            $fn_name ($($arg_name: $arg_ty),*) [$($ret_ty)?]
+                            [$($body)?]
                     }
                 )*
 
@@ -165,44 +801,96 @@ macro_rules! interface {
                 $crate::calculated_doc! {
                     #[doc = " Copy of [`IntoJsInterface::to`].\n"]
                     #[doc = "\n"]
+                    #[doc = " This is [`into_js_handle`] followed by"]
+                    #[doc = " [`InterfaceHandle::release`]: the Rust value is"]
+                    #[doc = " freed when the JS object's `dispose` method is"]
+                    #[doc = " called (and leaked if it has no such method).\n"]
+                    #[doc = "\n"]
                     #[doc = $crate::ext::_m_sprt::concat!(
                         " [`IntoJsInterface::to`]: ",
                             $crate::ext::_m_sprt::stringify!(/*$*/crate),
-                        "::ext::IntoJsInterface::to",
+                        "::ext::IntoJsInterface::to\n",
+                    )]
+                    #[doc = $crate::ext::_m_sprt::concat!(
+                        " [`into_js_handle`]: ",
+                            $crate::ext::_m_sprt::stringify!($nom),
+                        "::into_js_handle\n",
+                    )]
+                    #[doc = $crate::ext::_m_sprt::concat!(
+                        " [`InterfaceHandle::release`]: ",
+                            $crate::ext::_m_sprt::stringify!(/*$*/crate),
+                        "::ext::InterfaceHandle::release",
                     )]
                     >>>
                     fn into_js(self) -> $js_interface
                     where
                         Self: $crate::ext::_m_sprt::Sized + 'static,
                     {
-                        use $crate::ext::_m_sprt::{Box, JsCast};
-                        let b = Box::leak(Box::new(self));
-                        $nom::into_js_inner(b).unchecked_into()
+                        $nom::into_js_handle(self).release()
+                    }
+                }
+
+                $crate::calculated_doc! {
+                    #[doc = " Converts `self` into an instance of the JS"]
+                    #[doc = " interface that is backed by `self`.\n"]
+                    #[doc = "\n"]
+                    #[doc = " The returned handle owns `self` and the closures"]
+                    #[doc = " that make up the JS object; they're freed when"]
+                    #[doc = " the handle is dropped or when the JS object's"]
+                    #[doc = " `dispose` method (if it has one) is called."]
+                    >>>
+                    fn into_js_handle(
+                        self
+                    ) -> $crate::ext::InterfaceHandle<$js_interface>
+                    where
+                        Self: $crate::ext::_m_sprt::Sized + 'static,
+                    {
+                        use $crate::ext::_m_sprt::{
+                            Any, Box, JsCast, Object, Rc, RefCell, Vec,
+                        };
+
+                        let this = Rc::new(RefCell::new(self));
+                        let mut resources: Vec<Box<dyn Any>> = Vec::new();
+                        let obj = Object::new();
+                        $nom::into_js_inner(&this, &obj, &mut resources);
+                        resources.push(Box::new(this));
+
+                        $crate::ext::InterfaceHandle::new(
+                            obj.unchecked_into(),
+                            resources,
+                        )
                     }
                 }
 
                 $crate::calculated_doc! {
                     #[doc = $crate::ext::_m_sprt::concat!(
-                        " Internal version of [`into_js_by_ref`]",
+                        " Internal version of [`into_js_handle`]",
                         "(",
                             $crate::ext::_m_sprt::stringify!($nom),
-                            "::into_js_by_ref",
+                            "::into_js_handle",
                         ")",
-                        " that doesn't\n leak `self`.\n",
+                        " that puts\n the members of the interface on",
+                        " `obj`, adding the closures they're made of to\n",
+                        " `resources`.\n",
                     )]
                     #[doc = "\n"]
                     #[doc = " Useful for trait/interface hierarchies."]
                     >>>
                     fn into_js_inner(
-                        &'static self
-                    ) -> $crate::ext::_m_sprt::Object
+                        this: &$crate::ext::_m_sprt::Rc<
+                            $crate::ext::_m_sprt::RefCell<Self>
+                        >,
+                        obj: &$crate::ext::_m_sprt::Object,
+                        resources: &mut $crate::ext::_m_sprt::Vec<
+                            $crate::ext::_m_sprt::Box<
+                                dyn $crate::ext::_m_sprt::Any
+                            >
+                        >,
+                    )
                     where
-                        Self: 'static,
+                        Self: $crate::ext::_m_sprt::Sized + 'static,
                     {
-                        use $crate::ext::_m_sprt::{Box, Closure, Object};
-                        use $crate::ext::object;
-
-                        let base = Object::new();
+                        use $crate::ext::_m_sprt::AsRef;
 
                         // First, let's verify that all the functions are
                         // actually part of the JS interface.
@@ -213,53 +901,80 @@ macro_rules! interface {
 
                             #[doc(hidden)]
                             mod __check_that_the_interface_matches_the_trait {
+                                // The argument types need to be in scope but
+                                // the Rust trait must not be (otherwise
+                                // `Js::$fn_name` could resolve to the trait's
+                                // method), so we shadow it.
+                                #[allow(unused_imports)]
+                                use super::*;
                                 use super::__js_iface as Js;
 
+                                #[allow(dead_code)]
+                                struct $nom;
+
                                 $(
-                                    #[allow(non_upper_case_globals, dead_code)]
-                                    const $fn_name: () = {
-                                        let _ = Js::$fn_name;
-                                        ()
-                                    };
+                                    $crate::interface! {
+                                        @check [$($kind)*] Js $fn_name
+                                            ($($arg_ty),*) [$($ret_ty)?]
+                                    }
                                 )*
                             }
                         }
 
                         // Now we can begin. The things we extend, first:
                         $($(
-                            let base = Object::assign(
-                                &base,
-                                &<Self as $ext_rs>::into_js_inner(self)
+                            <Self as $ext_rs>::into_js_inner(
+                                this,
+                                obj,
+                                resources,
                             );
                         )*)?
 
-                        // Next, the functions of the interface:
-                        struct Inner {
-                            $($fn_name: Closure<dyn FnMut(
-                                    $($arg_ty,)*
-                                ) $(-> $ret_ty)?>
-                            ,)*
-                        }
-
-                        let Inner {
-                            $($fn_name,)*
-                        } = Inner {
-                            $($fn_name: {
-                                Closure::wrap(
-                                    Box::new(move |$($arg_name: $arg_ty, )*| {
-                                        Self::$fn_name(self $(, $arg_name)*)
-                                    })
-                                )
-                            },)*
-                        };
-
-                        let obj = object! { (base) += {
-                            $($fn_name: $fn_name),*
-                        }};
-
-                        $(Closure::forget($fn_name);)*
+                        // Next, the members of the interface. These go
+                        // directly on `obj` (rather than on a fresh object
+                        // that's merged in) so that getters stay getters.
+                        //
+                        // The closures are freed along with the handle.
+                        $(
+                            $crate::interface! {
+                                @install [$($kind)*] [$($recv_mut)?]
+                                    this obj resources
+                                    $fn_name ($($arg_name: $arg_ty),*)
+                                    [$($ret_ty)?]
+                            }
+                        )*
+                    }
+                }
 
-                        obj
+                $crate::calculated_doc! {
+                    #[doc = " A TypeScript declaration for the JS side of this"]
+                    #[doc = " trait (for JS code that uses an implementation"]
+                    #[doc = " of it).\n"]
+                    #[doc = "\n"]
+                    #[doc = $crate::ext::_m_sprt::concat!(
+                        " See the [`typescript` module](",
+                            $crate::ext::_m_sprt::stringify!(/*$*/crate),
+                        "::ext::typescript) for more information.",
+                    )]
+                    >>>
+                    fn ts_declaration() -> $crate::ext::TsDeclaration
+                    where
+                        Self: $crate::ext::_m_sprt::Sized,
+                    {
+                        $crate::ext::TsDeclaration::new(
+                            $crate::ext::_m_sprt::stringify!($js_interface)
+                        )
+                        $($(
+                            .extends(
+                                $crate::ext::_m_sprt::stringify!($ext_js)
+                            )
+                        )+)?
+                        $(
+                            .member($crate::interface! {
+                                @ts_member [$($kind)*] $fn_name
+                                    ($($arg_name: $arg_ty),*) [$($ret_ty)?]
+                            })
+                        )*
                     }
                 }
             }
@@ -358,108 +1073,9 @@ macro_rules! interface {
                 X: AsRef<$js_interface>,
             {
                 $(
-                    $crate::calculated_doc! {
-                        #[doc = $crate::ext::_m_sprt::concat!(
-                            " [`",
-                                $crate::ext::_m_sprt::stringify!($fn_name),
-                            "`](",
-                                $crate::ext::_m_sprt::stringify!($nom),
-                                "::",
-                                $crate::ext::_m_sprt::stringify!($fn_name),
-                            ")",
-                            " for types that implement the ",
-                            "[`",
-                                $crate::ext::_m_sprt::stringify!($js_interface),
-                            "`]",
-                            " interface.",
-                        )]
-                        >>>
-                        fn $fn_name(
-                            &self
-                            $(, $arg_name: $arg_ty)*
-                        ) $(-> $ret_ty)? {
-                            $js_interface::$fn_name(
-                                $crate::ext::_m_sprt::AsRef
-                                    ::<$js_interface>::as_ref(self),
-                                $($arg_name,)*
-                            )
-                        }
-
-                        // Unfortunately there doesn't seem to be syntax that
-                        // lets us say that, when there's ambiguity between
-                        // a trait method and an inherent method, use the
-                        // inherent method (we can, however, say to use the
-                        // trait method using FQS).
-                        //
-                        // That's a problem here because it means that we
-                        // fall back on the trait method that we're implementing
-                        // when the underlying inherent method we want to proxy
-                        // doesn't exist.
-                        //
-                        // We can still make this a hard error by forbidding
-                        // obviously infinitely recursion functions, but this
-                        // isn't perfect since it produces a pretty cryptic
-                        // error (we'd like to say that the method doesn't
-                        // exist on the JS interface). Alas, without a proc
-                        // macro or without naming the proxied methods something
-                        // different (I think this'd also need a proc macro or
-                        // the paste crate) this is probably as good as we can
-                        // do.
-                        //
-                        // This is mentioned in the macro's docs.
-                        //
-                        // Actually, one way we can do this kind of check is
-                        // to make a fake module (that's hidden) and to, within
-                        // it, not import the Rust trait but to import the JS
-                        // interface and then "use" the functions on the
-                        // interface we're after (not directly unfortunately —
-                        // structs don't work like that in Rust). We can use
-                        // them by doing something like:
-                        // ```rust
-                        // mod __testing {
-                        //   use super::$js_interface;
-                        //   const $fn_name: () = {
-                        //     let _ = $js_interface::$fn_name; ()
-                        //   };
-                        // }
-                        // ```
-                        // Unfortunately the `super::$js_interface` part won't
-                        // work if $js_interface is an absolute path and the
-                        // error message will (not so helpfully in this case)
-                        // suggest that we import the Rust trait into scope to
-                        // try to fix the problem which is possibly more
-                        // confusing in this case.
-                        //
-                        // So, I think we'll just leave it for now.
-                        //
-                        // Actually we can get around the above by having the
-                        // module above `use` $js_interface `as` something else
-                        // within a scope. So, let's do it!
-                        //
-                        // Okay! Done!
-                        //
-                        // Note that if you do things like have mismatched
-                        // parameters you might get the recursion error instead
-                        // (I _think_ if the inherent method is there it'll
-                        // resolve to it over the trait method, but I'm not
-                        // 100% sure).
-                        //
-                        // It's possible to expand the module hack above to
-                        // check for this too (you'd do something like:
-                        // ```rust
-                        // #[doc(hidden)]
-                        // fn $fn_name($($arg_name: arg_ty),*) $(-> $ret_ty)? {
-                        //   Js::$fn_name($($arg_name: arg_ty),*)
-                        // }
-                        // ```
-                        // which should error on any type/arity mismatches), but
-                        // I'll leave that for another day.
-                        //
-                        // Pretty sure about the inherent method thing and even
-                        // if that isn't true, we've still got the unconditional
-                        // recursion error.
-                        //
-                        #[forbid(unconditional_recursion)]
+                    $crate::interface! {
+                        @js_item [$($kind)*] [$($recv_mut)?] $js_interface $nom
+                            $fn_name ($($arg_name: $arg_ty),*) [$($ret_ty)?]
                     }
                 )*
 
@@ -524,6 +1140,31 @@ macro_rules! interface {
                         AsRef::<$js_interface>::as_ref(&self).clone()
                     }
                 }
+
+                $crate::calculated_doc! {
+                    #[doc = $crate::ext::_m_sprt::concat!(
+                        " [`into_js_handle`](",
+                            $crate::ext::_m_sprt::stringify!($nom),
+                        "::into_js_handle)",
+                        " for types that implement the\n ",
+                        "[`",
+                            $crate::ext::_m_sprt::stringify!($js_interface),
+                        "`]",
+                        " interface.\n",
+                    )]
+                    #[doc = "\n"]
+                    #[doc = " There's nothing for the handle to own."]
+                    >>>
+                    fn into_js_handle(
+                        self
+                    ) -> $crate::ext::InterfaceHandle<$js_interface> {
+                        use $crate::ext::_m_sprt::{AsRef, Clone};
+
+                        $crate::ext::InterfaceHandle::unowned(
+                            AsRef::<$js_interface>::as_ref(&self).clone()
+                        )
+                    }
+                }
             }
         }
     };
@@ -631,35 +1272,264 @@ pub trait IntoJsInterface<Interface: FromWasmAbi + IntoWasmAbi + JsCast> {
     /// instance.
     ///
     /// For Rust impls of a trait this will probably require `Self` to implement
-    /// `Clone` since as part of creating the instance the instance needs to be
-    /// leaked (for methods to still work), but we'll leave that up to
+    /// `Clone` since the instance of the interface needs to own the Rust
+    /// instance (for methods to still work), but we'll leave that up to
     /// implementors.
     fn by_ref(&self) -> Interface;
 }
 
+/// The resources backing an [`InterfaceHandle`]: `None` once they've been
+/// freed.
+type Resources = Rc<RefCell<Option<Vec<Box<dyn Any>>>>>;
+
+/// A JS object made from a Rust implementation of an interface (see
+/// [`interface!`]), along with the Rust value and closures that back it.
+///
+/// The Rust value and closures are freed when the handle is dropped, at which
+/// point the JS object stops working: calling its methods throws. If the JS
+/// object has a `dispose` method (i.e. it's an addon or anything else that's
+/// [`Disposable`]), calling it also frees everything (after the Rust
+/// implementation of `dispose` runs).
+///
+/// To hand ownership over to JS entirely (i.e. for an addon that xterm.js will
+/// dispose of), use [`InterfaceHandle::release`].
+///
+/// [`interface!`]: crate::interface
+pub struct InterfaceHandle<I> {
+    /// The JS object.
+    object: I,
+    /// The things backing `object`; `None` if there aren't any (i.e. for
+    /// actual JS implementations of the interface).
+    resources: Option<Resources>,
+    /// Whether `object`'s `dispose` method frees `resources`.
+    freed_on_dispose: bool,
+}
+
+impl<I: Debug> Debug for InterfaceHandle<I> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("InterfaceHandle")
+            .field("object", &self.object)
+            .field("resources", &self.resources)
+            .field("freed_on_dispose", &self.freed_on_dispose)
+            .finish()
+    }
+}
+
+impl<I: JsCast> InterfaceHandle<I> {
+    /// Wraps `object`, which is backed by `resources`.
+    ///
+    /// If `object` has a `dispose` method it's replaced with one that calls the
+    /// original and then frees `resources`.
+    pub fn new(object: I, resources: Vec<Box<dyn Any>>) -> Self {
+        let resources: Resources = Rc::new(RefCell::new(Some(resources)));
+
+        let key = JsValue::from_str("dispose");
+        let dispose = Reflect::get(object.as_ref(), &key)
+            .ok()
+            .and_then(|d| d.dyn_into::<Function>().ok());
+
+        let freed_on_dispose = if let Some(dispose) = dispose {
+            let target = object.as_ref().clone();
+            let owner = resources.clone();
+
+            // Note: this closure holds a reference to the resources it's a part
+            // of; the cycle is broken when the resources are freed.
+            let hook: Box<dyn FnMut()> = Box::new(move || {
+                let result = dispose.call0(&target);
+
+                let freed = owner.borrow_mut().take();
+                drop(freed);
+
+                if let Err(err) = result {
+                    wasm_bindgen::throw_val(err);
+                }
+            });
+            let hook = Closure::wrap(hook);
+
+            let _ = Reflect::set(object.as_ref(), &key, hook.as_ref());
+            if let Some(resources) = resources.borrow_mut().as_mut() {
+                resources.push(Box::new(hook));
+            }
+
+            true
+        } else {
+            false
+        };
+
+        Self {
+            object,
+            resources: Some(resources),
+            freed_on_dispose,
+        }
+    }
+
+    /// Wraps an `object` that isn't backed by anything that needs to be freed
+    /// (i.e. an actual JS implementation of an interface).
+    pub fn unowned(object: I) -> Self {
+        Self {
+            object,
+            resources: None,
+            freed_on_dispose: false,
+        }
+    }
+
+    /// Gives up ownership of the resources backing the JS object, returning
+    /// the object.
+    ///
+    /// If the object has a `dispose` method, the resources are freed when
+    /// it's called. Otherwise they are leaked.
+    pub fn release(mut self) -> I
+    where
+        I: Clone,
+    {
+        if let Some(resources) = self.resources.take() {
+            if !self.freed_on_dispose {
+                core::mem::forget(resources);
+            }
+        }
+
+        self.object.clone()
+    }
+}
+
+impl<I> InterfaceHandle<I> {
+    /// The JS object.
+    pub fn object(&self) -> &I {
+        &self.object
+    }
+
+    /// Whether the resources backing the JS object have been freed.
+    pub fn is_freed(&self) -> bool {
+        self.resources
+            .as_ref()
+            .map_or(false, |r| r.borrow().is_none())
+    }
+}
+
+impl<I> Deref for InterfaceHandle<I> {
+    type Target = I;
+
+    fn deref(&self) -> &I {
+        &self.object
+    }
+}
+
+impl<I> Drop for InterfaceHandle<I> {
+    fn drop(&mut self) {
+        if let Some(resources) = self.resources.take() {
+            let freed = resources.borrow_mut().take();
+            drop(freed);
+        }
+    }
+}
+
+/// What the Rust side of an `async` member of an [`interface!`] trait returns.
+///
+/// [`interface!`]: crate::interface
+pub type LocalBoxFuture<T> = Pin<Box<dyn Future<Output = T>>>;
+
+/// The output of an `async` member of an [`interface!`] trait: something that
+/// corresponds to the way a JS `Promise` settles.
+///
+/// This is implemented for `Result<T, E>` where both `T` and `E` are JS types;
+/// `Ok` is a resolved promise and `Err` is a rejected one.
+///
+/// [`interface!`]: crate::interface
+pub trait PromiseOutput: Sized {
+    /// Converts `self` into the value a promise is resolved with.
+    ///
+    /// # Errors
+    ///
+    /// Returns the value the promise should be rejected with instead.
+    fn into_settled(self) -> Result<JsValue, JsValue>;
+
+    /// Converts the value a promise was resolved (`Ok`) or rejected (`Err`)
+    /// with.
+    ///
+    /// The value is _not_ checked; as with the rest of the interface glue we
+    /// trust that the JS side returns what it's declared to.
+    fn from_settled(settled: Result<JsValue, JsValue>) -> Self;
+}
+
+impl<T: JsCast, E: JsCast> PromiseOutput for Result<T, E> {
+    fn into_settled(self) -> Result<JsValue, JsValue> {
+        self.map(Into::into).map_err(Into::into)
+    }
+
+    fn from_settled(settled: Result<JsValue, JsValue>) -> Self {
+        settled
+            .map(JsCast::unchecked_into)
+            .map_err(JsCast::unchecked_into)
+    }
+}
+
 #[doc(inline)]
 pub use super::{calculated_doc, interface, object};
 
+#[cfg(feature = "macros")]
+#[cfg_attr(all(docs, not(doctest)), doc(cfg(feature = "macros")))]
+#[doc(inline)]
+pub use xterm_js_sys_macros::xterm_interface;
+
 pub mod addon;
 pub use addon::*;
 
+pub mod addon_manager;
+pub use addon_manager::*;
+
 pub mod disposable;
 pub use disposable::*;
 
 pub mod event;
 pub use event::*;
 
+pub mod fit;
+pub use fit::*;
+
+pub mod flow_control;
+pub use flow_control::*;
+
+pub mod input;
+pub use input::*;
+
+pub mod key;
+pub use key::*;
+
+pub mod keyboard;
+pub use keyboard::*;
+
+pub mod link;
+pub use link::*;
+
 pub mod log_level;
 pub use log_level::*;
 
 pub mod marker;
 pub use marker::*;
 
+pub mod mode;
+pub use mode::*;
+
 pub mod options;
 pub use options::*;
 
+pub mod paste;
+pub use paste::*;
+
+pub mod serialize;
+pub use serialize::*;
+
+pub mod terminal_event;
+pub use terminal_event::*;
+
 pub mod theme;
 pub use theme::*;
 
+pub mod typescript;
+pub use typescript::*;
+
 pub mod unicode;
 pub use unicode::*;
+
+pub mod write;
+pub use write::*;
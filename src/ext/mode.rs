@@ -0,0 +1,317 @@
+//! Tracking of the terminal modes programs set with DECSET and DECRST.
+//!
+//! xterm.js doesn't tell us which modes the program running in the terminal
+//! has turned on (i.e. whether it wants mouse reports or bracketed pastes). A
+//! [`ModeTracker`] (see [`Terminal::track_modes`]) watches the `CSI ? Pm h`
+//! and `CSI ? Pm l` sequences the program writes (without stopping xterm.js
+//! from handling them) and keeps a [`TerminalModes`] up to date.
+//!
+//! Only the modes that are interesting to embedders are tracked; see the
+//! fields of [`TerminalModes`].
+
+use super::{csi_params, CsiHandler, EventListener};
+use crate::xterm::{BufferType, FunctionIdentifier, Terminal};
+use crate::ReadOnlyArray;
+
+use wasm_bindgen::prelude::*;
+
+use core::cell::{Cell, RefCell};
+use core::fmt::{self, Debug};
+use std::rc::Rc;
+
+/// Which mouse events the program has asked to be reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseTracking {
+    /// No mouse reporting.
+    Off,
+    /// Button presses only (mode 9).
+    X10,
+    /// Button presses and releases (mode 1000).
+    Normal,
+    /// Presses, releases and movement while a button is held (mode 1002).
+    ButtonEvent,
+    /// Presses, releases and all movement (mode 1003).
+    AnyEvent,
+}
+
+/// How the program has asked for mouse reports to be encoded.
+///
+/// The [input decoder](super::input) understands all of these.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseEncoding {
+    /// The X10 encoding.
+    Default,
+    /// The UTF-8 extended encoding (mode 1005).
+    Utf8,
+    /// The SGR encoding (mode 1006).
+    Sgr,
+    /// The URXVT encoding (mode 1015).
+    Urxvt,
+}
+
+/// The DEC private modes a program has set.
+#[allow(clippy::module_name_repetitions, clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TerminalModes {
+    /// Whether the cursor keys send application sequences (`DECCKM`, mode 1).
+    pub application_cursor_keys: bool,
+    /// Which mouse events are reported.
+    pub mouse_tracking: MouseTracking,
+    /// How mouse reports are encoded.
+    pub mouse_encoding: MouseEncoding,
+    /// Whether focus in/out is reported (mode 1004).
+    pub focus_reporting: bool,
+    /// Whether the alternate screen is active (modes 47, 1047 and 1049).
+    pub alternate_screen: bool,
+    /// Whether pastes are wrapped in bracketed paste markers (mode 2004).
+    pub bracketed_paste: bool,
+}
+
+impl Default for TerminalModes {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl TerminalModes {
+    /// The modes a terminal starts out with.
+    pub const DEFAULT: Self = Self {
+        application_cursor_keys: false,
+        mouse_tracking: MouseTracking::Off,
+        mouse_encoding: MouseEncoding::Default,
+        focus_reporting: false,
+        alternate_screen: false,
+        bracketed_paste: false,
+    };
+
+    /// Applies a DECSET (`enabled = true`) or DECRST (`enabled = false`) for a
+    /// single private mode.
+    ///
+    /// Returns whether the mode is one that is tracked.
+    pub fn set_private_mode(&mut self, mode: u32, enabled: bool) -> bool {
+        let tracking = match mode {
+            9 => MouseTracking::X10,
+            1000 => MouseTracking::Normal,
+            1002 => MouseTracking::ButtonEvent,
+            1003 => MouseTracking::AnyEvent,
+            _ => MouseTracking::Off,
+        };
+        let encoding = match mode {
+            1005 => MouseEncoding::Utf8,
+            1006 => MouseEncoding::Sgr,
+            1015 => MouseEncoding::Urxvt,
+            _ => MouseEncoding::Default,
+        };
+
+        match mode {
+            1 => self.application_cursor_keys = enabled,
+            1004 => self.focus_reporting = enabled,
+            47 | 1047 | 1049 => self.alternate_screen = enabled,
+            2004 => self.bracketed_paste = enabled,
+
+            // Like xterm.js, turning off any of the mouse tracking modes turns
+            // off mouse tracking altogether.
+            9 | 1000 | 1002 | 1003 => {
+                self.mouse_tracking = if enabled {
+                    tracking
+                } else {
+                    MouseTracking::Off
+                };
+            }
+            1005 | 1006 | 1015 => {
+                if enabled {
+                    self.mouse_encoding = encoding;
+                } else if self.mouse_encoding == encoding {
+                    self.mouse_encoding = MouseEncoding::Default;
+                }
+            }
+
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// The DECSET sequences that turn these modes on in a terminal that has
+    /// the [default modes](TerminalModes::DEFAULT).
+    ///
+    /// [`alternate_screen`](TerminalModes::alternate_screen) is left out;
+    /// switching screens is only useful along with the screen's contents (see
+    /// [`SerializeAddon`](super::SerializeAddon)).
+    #[must_use]
+    pub fn to_ansi(&self) -> String {
+        let tracking = match self.mouse_tracking {
+            MouseTracking::Off => None,
+            MouseTracking::X10 => Some(9),
+            MouseTracking::Normal => Some(1000),
+            MouseTracking::ButtonEvent => Some(1002),
+            MouseTracking::AnyEvent => Some(1003),
+        };
+        let encoding = match self.mouse_encoding {
+            MouseEncoding::Default => None,
+            MouseEncoding::Utf8 => Some(1005),
+            MouseEncoding::Sgr => Some(1006),
+            MouseEncoding::Urxvt => Some(1015),
+        };
+
+        [
+            Some(1).filter(|_| self.application_cursor_keys),
+            tracking,
+            encoding,
+            Some(1004).filter(|_| self.focus_reporting),
+            Some(2004).filter(|_| self.bracketed_paste),
+        ]
+        .iter()
+        .flatten()
+        .map(|mode| format!("\x1b[?{}h", mode))
+        .collect()
+    }
+}
+
+/// A listener for mode changes.
+type ModeListener = dyn FnMut(&TerminalModes);
+
+/// State shared between a [`ModeTracker`] and its parser hooks.
+struct ModeState {
+    /// The current modes.
+    modes: Cell<TerminalModes>,
+    /// Called whenever the modes change.
+    listeners: RefCell<Vec<Box<ModeListener>>>,
+}
+
+impl ModeState {
+    /// Updates the modes with `update`, notifying the listeners if anything
+    /// changed.
+    fn update<U: FnOnce(&mut TerminalModes)>(&self, update: U) {
+        let mut modes = self.modes.get();
+        update(&mut modes);
+
+        if modes == self.modes.replace(modes) {
+            return;
+        }
+
+        // Listeners are taken out while they run so that they can add other
+        // listeners.
+        let mut listeners = self.listeners.replace(Vec::new());
+        for listener in &mut listeners {
+            listener(&modes);
+        }
+
+        let mut current = self.listeners.borrow_mut();
+        listeners.append(&mut current);
+        *current = listeners;
+    }
+}
+
+/// Tracks the [`TerminalModes`] of a [`Terminal`].
+///
+/// Created with [`Terminal::track_modes`]. Dropping this removes its parser
+/// hooks.
+pub struct ModeTracker {
+    /// The modes and change listeners.
+    state: Rc<ModeState>,
+    /// The DECSET and DECRST handlers.
+    handlers: Vec<EventListener<CsiHandler>>,
+    /// Resets the modes on a full reset (`RIS`).
+    reset_handler: EventListener<dyn FnMut() -> bool>,
+}
+
+impl Debug for ModeTracker {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("ModeTracker")
+            .field("modes", &self.state.modes.get())
+            .field("listeners", &self.state.listeners.borrow().len())
+            .field("handlers", &self.handlers)
+            .field("reset_handler", &self.reset_handler)
+            .finish()
+    }
+}
+
+impl ModeTracker {
+    /// The current modes.
+    #[must_use]
+    pub fn modes(&self) -> TerminalModes {
+        self.state.modes.get()
+    }
+
+    /// Calls `listener` with the new modes whenever they change.
+    ///
+    /// A sequence that changes several modes at once (i.e. `CSI ? 1000 ; 1006
+    /// h`) only results in one call.
+    pub fn on_change<F: FnMut(&TerminalModes) + 'static>(&self, listener: F) {
+        self.state.listeners.borrow_mut().push(Box::new(listener));
+    }
+}
+
+impl Terminal {
+    /// Starts tracking the terminal's modes.
+    ///
+    /// xterm.js 4.6 doesn't let us read the modes that are already set so the
+    /// tracker starts out with the [default modes](TerminalModes::DEFAULT)
+    /// (except for [`alternate_screen`], which is read from the active buffer);
+    /// create the tracker before anything is written to the terminal.
+    ///
+    /// [`alternate_screen`]: TerminalModes::alternate_screen
+    #[must_use]
+    pub fn track_modes(&self) -> ModeTracker {
+        let modes = TerminalModes {
+            alternate_screen: self.buffer().active().r#type()
+                == BufferType::Alternate,
+            ..TerminalModes::DEFAULT
+        };
+        let state = Rc::new(ModeState {
+            modes: Cell::new(modes),
+            listeners: RefCell::new(Vec::new()),
+        });
+
+        let parser = self.parser();
+        let handlers = [true, false]
+            .iter()
+            .map(|&enabled| {
+                let state = state.clone();
+                let handler = move |params: ReadOnlyArray<JsValue>| {
+                    let params = csi_params(&params);
+                    state.update(|modes| {
+                        for mode in params {
+                            let _ = modes.set_private_mode(mode, enabled);
+                        }
+                    });
+
+                    // Let xterm.js handle the sequence too.
+                    false
+                };
+
+                let handler: Box<CsiHandler> = Box::new(handler);
+                EventListener::new(Closure::wrap(handler), |closure| {
+                    parser.register_csi_handler(
+                        FunctionIdentifier::new(
+                            Some("?".to_string()),
+                            None,
+                            (if enabled { "h" } else { "l" }).to_string(),
+                        ),
+                        closure,
+                    )
+                })
+            })
+            .collect();
+
+        let reset_state = state.clone();
+        let reset_handler: Box<dyn FnMut() -> bool> = Box::new(move || {
+            reset_state.update(|modes| *modes = TerminalModes::DEFAULT);
+            false
+        });
+        let reset_handler =
+            EventListener::new(Closure::wrap(reset_handler), |closure| {
+                parser.register_esc_handler(
+                    FunctionIdentifier::new(None, None, "c".to_string()),
+                    closure,
+                )
+            });
+
+        ModeTracker {
+            state,
+            handlers,
+            reset_handler,
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! Sanitization for pasted text.
+//!
+//! [`Terminal::paste`] hands the program whatever text it's given. Text copied
+//! from a web page can contain things the user can't see: escape sequences
+//! (i.e. a bracketed paste end marker followed by a command, which defeats
+//! bracketed paste entirely) or trailing newlines that run a command as soon
+//! as it's pasted. This is known as "paste-jacking".
+//!
+//! A [`PastePolicy`] decides what to do about this; use it with
+//! [`Terminal::paste_sanitized`], or with [`PastePolicy::prepare`] if you're
+//! sending the data to the program yourself.
+
+use super::TerminalModes;
+use crate::xterm::Terminal;
+
+use core::fmt::{self, Debug};
+
+/// The bracketed paste start marker.
+const PASTE_START: &str = "\x1b[200~";
+/// The bracketed paste end marker.
+const PASTE_END: &str = "\x1b[201~";
+
+/// What to do with control characters in pasted text.
+///
+/// Tabs and newlines are not considered control characters here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ControlCharacters {
+    /// Remove them.
+    Strip,
+    /// Replace them with a visible representation in caret notation (i.e.
+    /// `ESC` becomes `^[`); C1 control characters become `\u{XX}`.
+    Escape,
+}
+
+/// Asked whether a paste containing newlines should go through.
+type ConfirmMultiline = dyn FnMut(&str) -> bool;
+
+/// How to sanitize pasted text.
+pub struct PastePolicy {
+    /// What to do with control characters (including `ESC`).
+    pub control_characters: ControlCharacters,
+    /// Whether to turn `\r\n` and `\n` into `\r` (what the Enter key sends).
+    pub normalize_newlines: bool,
+    /// Asked whether a paste containing newlines should go through.
+    confirm_multiline: Option<Box<ConfirmMultiline>>,
+}
+
+impl Debug for PastePolicy {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("PastePolicy")
+            .field("control_characters", &self.control_characters)
+            .field("normalize_newlines", &self.normalize_newlines)
+            .field("confirm_multiline", &self.confirm_multiline.is_some())
+            .finish()
+    }
+}
+
+impl Default for PastePolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PastePolicy {
+    /// Strips control characters and normalizes newlines; multi-line pastes
+    /// are allowed without confirmation.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            control_characters: ControlCharacters::Strip,
+            normalize_newlines: true,
+            confirm_multiline: None,
+        }
+    }
+
+    /// Sets the function that's asked whether a paste that contains newlines
+    /// should go through.
+    ///
+    /// The function is given the sanitized text and should return `true` to
+    /// allow the paste (i.e. after asking the user).
+    pub fn on_multiline<F>(&mut self, confirm: F) -> &mut Self
+    where
+        F: FnMut(&str) -> bool + 'static,
+    {
+        self.confirm_multiline = Some(Box::new(confirm));
+        self
+    }
+
+    /// Sanitizes `text`.
+    ///
+    /// Returns `None` if the paste contains newlines and the
+    /// [multi-line confirmation](PastePolicy::on_multiline) rejected it.
+    pub fn sanitize(&mut self, text: &str) -> Option<String> {
+        let mut out = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\r' if self.normalize_newlines => {
+                    if chars.peek() == Some(&'\n') {
+                        let _ = chars.next();
+                    }
+                    out.push('\r');
+                }
+                '\n' if self.normalize_newlines => out.push('\r'),
+                '\r' | '\n' | '\t' => out.push(c),
+
+                c if c.is_control() => match self.control_characters {
+                    ControlCharacters::Strip => {}
+                    ControlCharacters::Escape => match c as u32 {
+                        code @ 0..=0x1f => {
+                            out.push('^');
+                            #[allow(clippy::cast_possible_truncation)]
+                            out.push(char::from(code as u8 + b'@'));
+                        }
+                        0x7f => out.push_str("^?"),
+                        code => out.push_str(&format!("\\u{{{:X}}}", code)),
+                    },
+                },
+
+                c => out.push(c),
+            }
+        }
+
+        if out.contains(&['\r', '\n'][..]) {
+            if let Some(confirm) = self.confirm_multiline.as_mut() {
+                if !confirm(&out) {
+                    return None;
+                }
+            }
+        }
+
+        Some(out)
+    }
+
+    /// Sanitizes `text` and wraps it in bracketed paste markers if `modes`
+    /// says the program has enabled bracketed paste; this is the data to send
+    /// the program.
+    ///
+    /// `modes` typically comes from a [`ModeTracker`](super::ModeTracker).
+    pub fn prepare(
+        &mut self,
+        text: &str,
+        modes: &TerminalModes,
+    ) -> Option<String> {
+        let text = self.sanitize(text)?;
+
+        Some(if modes.bracketed_paste {
+            format!("{}{}{}", PASTE_START, text, PASTE_END)
+        } else {
+            text
+        })
+    }
+}
+
+impl Terminal {
+    /// Like [`Terminal::paste`] but sanitizes `text` with `policy` first.
+    ///
+    /// xterm.js adds the bracketed paste markers itself when the program has
+    /// enabled bracketed paste (the same mode a [`ModeTracker`] tracks), so
+    /// they aren't added here.
+    ///
+    /// Returns `false` if the paste was rejected by the policy's
+    /// [multi-line confirmation](PastePolicy::on_multiline).
+    ///
+    /// [`ModeTracker`]: super::ModeTracker
+    pub fn paste_sanitized(
+        &self,
+        text: &str,
+        policy: &mut PastePolicy,
+    ) -> bool {
+        match policy.sanitize(text) {
+            Some(text) => {
+                self.paste(text);
+                true
+            }
+            None => false,
+        }
+    }
+}
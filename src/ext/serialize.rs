@@ -0,0 +1,569 @@
+//! A Rust version of the [serialize addon], which turns a [`Terminal`]'s
+//! buffers into the escape sequences that recreate them.
+//!
+//! [`SerializeAddon`] is an [`XtermAddon`] so it's loaded like any other addon
+//! (i.e. with [`Terminal::load_xterm_addon`]). The serialization itself is done
+//! by [`BufferSnapshot::to_ansi`], which works on a copy of a buffer rather than
+//! on xterm.js's buffer.
+//!
+//! [`SerializeAddon::save`] goes a step further and also captures the size of
+//! the terminal and the options that affect how it's rendered; the resulting
+//! [`SerializedSession`] can be stored (as JSON) and
+//! [restored](SerializeAddon::restore) after a page reload.
+//!
+//! [serialize addon]: https://www.npmjs.com/package/xterm-addon-serialize
+
+use super::{ModeTracker, Terminal, XtermAddon, XtermDisposable};
+use crate::xterm::{Buffer, BufferCell, BufferType};
+
+use js_sys::{Array, Object, Reflect, JSON};
+use wasm_bindgen::{JsCast, JsValue};
+
+use core::cell::RefCell;
+use core::convert::TryFrom;
+use std::rc::Rc;
+
+/// The color of a cell's foreground or background.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CellColor {
+    /// The terminal's default color.
+    Default,
+    /// One of the 256 palette colors.
+    Palette(u8),
+    /// A 'true color' (red, green, and blue).
+    Rgb(u8, u8, u8),
+}
+
+impl CellColor {
+    /// The SGR parameters that select this color, given the parameter for the
+    /// first of the 8 basic colors (30 for the foreground, 40 for the
+    /// background).
+    fn sgr(self, base: u8) -> Option<String> {
+        match self {
+            CellColor::Default => None,
+            CellColor::Palette(n) if n < 8 => Some((base + n).to_string()),
+            CellColor::Palette(n) if n < 16 => {
+                Some((base + 60 + n - 8).to_string())
+            }
+            CellColor::Palette(n) => Some(format!("{};5;{}", base + 8, n)),
+            CellColor::Rgb(r, g, b) => {
+                Some(format!("{};2;{};{};{}", base + 8, r, g, b))
+            }
+        }
+    }
+}
+
+/// The colors and attributes of a cell.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CellStyle {
+    /// The foreground color.
+    pub fg: CellColor,
+    /// The background color.
+    pub bg: CellColor,
+    /// Bold (SGR 1).
+    pub bold: bool,
+    /// Dim (SGR 2).
+    pub dim: bool,
+    /// Italic (SGR 3).
+    pub italic: bool,
+    /// Underlined (SGR 4).
+    pub underline: bool,
+    /// Blinking (SGR 5).
+    pub blink: bool,
+    /// Inverse (SGR 7).
+    pub inverse: bool,
+    /// Invisible (SGR 8).
+    pub invisible: bool,
+}
+
+impl Default for CellStyle {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl CellStyle {
+    /// The style of a cell that no SGR sequence has touched.
+    pub const DEFAULT: Self = Self {
+        fg: CellColor::Default,
+        bg: CellColor::Default,
+        bold: false,
+        dim: false,
+        italic: false,
+        underline: false,
+        blink: false,
+        inverse: false,
+        invisible: false,
+    };
+
+    /// Reads the style of an xterm.js cell.
+    #[must_use]
+    pub fn of(cell: &BufferCell) -> Self {
+        let color = |rgb: bool, palette: bool, color: u32| {
+            if rgb {
+                let [_, r, g, b] = color.to_be_bytes();
+                CellColor::Rgb(r, g, b)
+            } else if palette {
+                u8::try_from(color)
+                    .map_or(CellColor::Default, CellColor::Palette)
+            } else {
+                CellColor::Default
+            }
+        };
+
+        Self {
+            fg: color(
+                cell.is_fg_rgb(),
+                cell.is_fg_palette(),
+                cell.get_fg_color(),
+            ),
+            bg: color(
+                cell.is_bg_rgb(),
+                cell.is_bg_palette(),
+                cell.get_bg_color(),
+            ),
+            bold: cell.is_bold(),
+            dim: cell.is_dim(),
+            italic: cell.is_italic(),
+            underline: cell.is_underline(),
+            blink: cell.is_blink(),
+            inverse: cell.is_inverse(),
+            invisible: cell.is_invisible(),
+        }
+    }
+
+    /// The SGR sequence that switches to this style from any other style.
+    fn sgr(&self) -> String {
+        let mut params = vec!["0".to_string()];
+        let flags = [
+            (self.bold, "1"),
+            (self.dim, "2"),
+            (self.italic, "3"),
+            (self.underline, "4"),
+            (self.blink, "5"),
+            (self.inverse, "7"),
+            (self.invisible, "8"),
+        ];
+        params.extend(
+            flags
+                .iter()
+                .filter(|(set, _)| *set)
+                .map(|(_, param)| (*param).to_string()),
+        );
+        params.extend(self.fg.sgr(30));
+        params.extend(self.bg.sgr(40));
+
+        format!("\x1b[{}m", params.join(";"))
+    }
+}
+
+/// A copy of a cell in a [`BufferSnapshot`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SnapshotCell {
+    /// The characters in the cell; empty for cells nothing was written to.
+    pub chars: String,
+    /// The number of columns the characters take up; 0 for the cell after a
+    /// wide character.
+    pub width: u8,
+    /// The cell's colors and attributes.
+    pub style: CellStyle,
+}
+
+impl SnapshotCell {
+    /// Whether the cell looks like a cell that nothing was written to.
+    fn is_blank(&self) -> bool {
+        self.chars.trim().is_empty() && self.style == CellStyle::DEFAULT
+    }
+}
+
+/// A copy of a line in a [`BufferSnapshot`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SnapshotLine {
+    /// The cells in the line.
+    pub cells: Vec<SnapshotCell>,
+    /// Whether the line is a continuation of the line before it.
+    pub wrapped: bool,
+}
+
+/// A copy of (the end of) a terminal buffer.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BufferSnapshot {
+    /// The lines; the last lines are the ones in the viewport.
+    pub lines: Vec<SnapshotLine>,
+    /// The cursor's column.
+    pub cursor_x: u16,
+    /// The cursor's row, relative to the viewport.
+    pub cursor_y: u16,
+}
+
+impl BufferSnapshot {
+    /// Copies the viewport of `buffer` (which is `rows` rows tall) along with
+    /// up to `scrollback` lines of scrollback (all of it if `None`).
+    #[must_use]
+    pub fn capture(
+        buffer: &Buffer,
+        rows: u16,
+        scrollback: Option<u32>,
+    ) -> Self {
+        let length = buffer.length();
+        let start = scrollback.map_or(0, |scrollback| {
+            length.saturating_sub(u32::from(rows).saturating_add(scrollback))
+        });
+
+        // xterm.js can fill in a cell we give it instead of making a new one.
+        let scratch = buffer.get_null_cell();
+        let lines = (start..length)
+            .filter_map(|y| buffer.get_line(y))
+            .map(|line| SnapshotLine {
+                cells: (0..line.length())
+                    .filter_map(|x| line.get_cell(x, Some(scratch.clone())))
+                    .map(|cell| SnapshotCell {
+                        chars: cell.get_chars(),
+                        width: cell.get_width(),
+                        style: CellStyle::of(&cell),
+                    })
+                    .collect(),
+                wrapped: line.is_wrapped(),
+            })
+            .collect();
+
+        Self {
+            lines,
+            cursor_x: buffer.cursor_x(),
+            cursor_y: buffer.cursor_y(),
+        }
+    }
+
+    /// The escape sequences that recreate the snapshot when written to an
+    /// empty terminal of the same size, ending with the cursor where it was.
+    ///
+    /// Blank cells at the end of a line are left out (unless the line wraps
+    /// onto the next one) and the style is reset before each line break so
+    /// that new lines aren't filled in with a background color.
+    #[must_use]
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::new();
+        let mut style = CellStyle::DEFAULT;
+
+        for (idx, line) in self.lines.iter().enumerate() {
+            if idx != 0 && !line.wrapped {
+                if style != CellStyle::DEFAULT {
+                    out.push_str("\x1b[0m");
+                    style = CellStyle::DEFAULT;
+                }
+                out.push_str("\r\n");
+            }
+
+            let wraps = self.lines.get(idx + 1).map_or(false, |l| l.wrapped);
+            let end = if wraps {
+                line.cells.len()
+            } else {
+                line.cells
+                    .iter()
+                    .rposition(|cell| !cell.is_blank())
+                    .map_or(0, |last| last + 1)
+            };
+
+            for cell in line.cells[..end].iter().filter(|cell| cell.width != 0)
+            {
+                if cell.style != style {
+                    style = cell.style;
+                    out.push_str(&style.sgr());
+                }
+
+                if cell.chars.is_empty() {
+                    out.push(' ');
+                } else {
+                    out.push_str(&cell.chars);
+                }
+            }
+        }
+
+        if style != CellStyle::DEFAULT {
+            out.push_str("\x1b[0m");
+        }
+
+        format!(
+            "{}\x1b[{};{}H",
+            out,
+            u32::from(self.cursor_y) + 1,
+            u32::from(self.cursor_x) + 1
+        )
+    }
+}
+
+/// Options for [`SerializeAddon::serialize`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[allow(clippy::module_name_repetitions)]
+pub struct SerializeOptions {
+    /// How many lines of scrollback to include; all of them if `None`.
+    pub scrollback: Option<u32>,
+    /// Leave out the sequences that restore the terminal's modes (i.e. mouse
+    /// tracking and bracketed paste).
+    pub exclude_modes: bool,
+    /// Leave out the alternate buffer, even if it's active.
+    pub exclude_alt_buffer: bool,
+}
+
+/// The [`TerminalOptions`](crate::xterm::TerminalOptions) that are saved in a
+/// [`SerializedSession`]; the ones that affect how the terminal's contents are
+/// laid out and rendered.
+pub const SESSION_OPTIONS: &[&str] = &[
+    "cursorBlink",
+    "cursorStyle",
+    "cursorWidth",
+    "drawBoldTextInBrightColors",
+    "fontFamily",
+    "fontSize",
+    "fontWeight",
+    "fontWeightBold",
+    "letterSpacing",
+    "lineHeight",
+    "minimumContrastRatio",
+    "scrollback",
+    "tabStopWidth",
+    "theme",
+];
+
+/// A serialized terminal along with its size and rendering options.
+///
+/// Made with [`SerializeAddon::save`]; restored with
+/// [`SerializeAddon::restore`] (or [`SerializedSession::restore`]).
+#[derive(Debug, Clone)]
+pub struct SerializedSession {
+    /// The output of [`SerializeAddon::serialize`].
+    pub data: String,
+    /// The number of columns the terminal had.
+    pub cols: u16,
+    /// The number of rows the terminal had.
+    pub rows: u16,
+    /// The terminal's [`SESSION_OPTIONS`], keyed by name.
+    pub options: Object,
+}
+
+impl SerializedSession {
+    /// Captures `terminal`'s size and [`SESSION_OPTIONS`] along with `data`.
+    #[must_use]
+    pub fn capture(terminal: &Terminal, data: String) -> Self {
+        let options = Object::new();
+        for key in SESSION_OPTIONS {
+            let value = terminal.get_option(key);
+            if !value.is_undefined() {
+                let _ = Reflect::set(&options, &JsValue::from_str(key), &value);
+            }
+        }
+
+        Self {
+            data,
+            cols: terminal.cols(),
+            rows: terminal.rows(),
+            options,
+        }
+    }
+
+    /// Encodes the session as JSON (i.e. to put in `localStorage`).
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let obj = Object::new();
+        let fields = [
+            ("data", JsValue::from_str(&self.data)),
+            ("cols", JsValue::from(self.cols)),
+            ("rows", JsValue::from(self.rows)),
+            ("options", self.options.clone().into()),
+        ];
+        for (key, value) in &fields {
+            let _ = Reflect::set(&obj, &JsValue::from_str(key), value);
+        }
+
+        JSON::stringify(&obj)
+            .ok()
+            .and_then(|json| json.as_string())
+            .unwrap_or_default()
+    }
+
+    /// Decodes a session that was encoded with [`SerializedSession::to_json`].
+    ///
+    /// Returns `None` if `json` isn't a session or if its size isn't a whole
+    /// number of columns and rows between 1 and `u16::MAX`.
+    #[must_use]
+    pub fn from_json(json: &str) -> Option<Self> {
+        let obj = JSON::parse(json).ok()?;
+        let field =
+            |key: &str| Reflect::get(&obj, &JsValue::from_str(key)).ok();
+
+        // The range is checked by hand (rather than leaning on saturating
+        // casts, which are new in Rust 1.45) since this is untrusted input;
+        // NaN fails the comparisons.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let size = |key: &str| {
+            field(key)?
+                .as_f64()
+                .filter(|n| {
+                    *n >= 1.0 && *n <= f64::from(u16::MAX) && n.fract() == 0.0
+                })
+                .map(|n| n as u16)
+        };
+
+        Some(Self {
+            data: field("data")?.as_string()?,
+            cols: size("cols")?,
+            rows: size("rows")?,
+            options: field("options")?.dyn_into().ok()?,
+        })
+    }
+
+    /// Resets `terminal` and then restores this session's options, size, and
+    /// contents.
+    pub fn restore(&self, terminal: &Terminal) {
+        terminal.reset();
+
+        let options = Object::entries(&self.options);
+        for entry in options.iter() {
+            let entry: Array = entry.unchecked_into();
+            if let Some(key) = entry.get(0).as_string() {
+                terminal.set_option(&key, &entry.get(1));
+            }
+        }
+        terminal.resize(self.cols, self.rows);
+
+        // `reset` doesn't go through the parser so mode trackers (like the
+        // serialize addon's) don't see it; a full reset (`RIS`) does.
+        terminal.write_str("\x1bc");
+        terminal.write_str(&self.data);
+    }
+}
+
+/// The state of a loaded [`SerializeAddon`].
+#[derive(Debug)]
+struct Loaded {
+    /// The terminal the addon was activated with.
+    terminal: Terminal,
+    /// Tracks the terminal's modes so they can be serialized.
+    modes: ModeTracker,
+}
+
+/// Serializes a [`Terminal`]'s contents and modes into escape sequences.
+///
+/// xterm.js doesn't expose the terminal's modes, so the addon tracks them
+/// from the moment it's loaded (see [`Terminal::track_modes`]); load it
+/// before anything is written to the terminal.
+///
+/// Clones share the terminal the addon was activated with, so a clone can be
+/// kept around to call [`SerializeAddon::serialize`] after the addon is
+/// loaded.
+///
+/// ```rust,no_run
+/// # use xterm_js_sys::{ext::{SerializeAddon, SerializeOptions, SerializedSession}, xterm::Terminal};
+/// # let terminal = Terminal::new(None);
+/// let serializer = SerializeAddon::new();
+/// terminal.load_xterm_addon(&serializer);
+///
+/// let json = serializer
+///     .save(&SerializeOptions { scrollback: Some(1000), ..Default::default() })
+///     .unwrap()
+///     .to_json();
+///
+/// // ...and after a reload:
+/// if let Some(session) = SerializedSession::from_json(&json) {
+///     serializer.restore(&session);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+#[allow(clippy::module_name_repetitions)]
+pub struct SerializeAddon {
+    /// The terminal the addon was activated with (if it's active).
+    loaded: Rc<RefCell<Option<Loaded>>>,
+}
+
+impl SerializeAddon {
+    /// Creates a serialize addon; it does nothing until it's loaded into a
+    /// terminal.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The escape sequences that recreate the terminal's buffers (and, unless
+    /// they're excluded, its modes).
+    ///
+    /// If the alternate buffer is active (and isn't excluded) the normal buffer
+    /// comes first, followed by a switch to the alternate buffer and its
+    /// contents.
+    ///
+    /// Returns `None` if the addon isn't active.
+    #[must_use]
+    pub fn serialize(&self, options: &SerializeOptions) -> Option<String> {
+        let loaded = self.loaded.borrow();
+        let Loaded { terminal, modes } = loaded.as_ref()?;
+
+        let rows = terminal.rows();
+        let buffers = terminal.buffer();
+        let mut out = BufferSnapshot::capture(
+            &buffers.normal(),
+            rows,
+            options.scrollback,
+        )
+        .to_ansi();
+
+        if !options.exclude_alt_buffer
+            && buffers.active().r#type() == BufferType::Alternate
+        {
+            out.push_str("\x1b[?1049h\x1b[H");
+            out.push_str(
+                &BufferSnapshot::capture(&buffers.alternate(), rows, Some(0))
+                    .to_ansi(),
+            );
+        }
+
+        if !options.exclude_modes {
+            out.push_str(&modes.modes().to_ansi());
+        }
+
+        Some(out)
+    }
+
+    /// [Serializes](SerializeAddon::serialize) the terminal and captures its
+    /// size and rendering options.
+    ///
+    /// Returns `None` if the addon isn't active.
+    #[must_use]
+    pub fn save(
+        &self,
+        options: &SerializeOptions,
+    ) -> Option<SerializedSession> {
+        let data = self.serialize(options)?;
+        let loaded = self.loaded.borrow();
+
+        Some(SerializedSession::capture(&loaded.as_ref()?.terminal, data))
+    }
+
+    /// Resets the terminal and restores `session` into it.
+    ///
+    /// Does nothing if the addon isn't active.
+    pub fn restore(&self, session: &SerializedSession) {
+        // Cloned so the terminal isn't borrowed while xterm.js runs.
+        let terminal = self
+            .loaded
+            .borrow()
+            .as_ref()
+            .map(|loaded| loaded.terminal.clone());
+
+        if let Some(terminal) = terminal {
+            session.restore(&terminal);
+        }
+    }
+}
+
+impl XtermDisposable for SerializeAddon {
+    fn dispose(&self) {
+        let _ = self.loaded.borrow_mut().take();
+    }
+}
+
+impl XtermAddon for SerializeAddon {
+    fn activate(&self, terminal: Terminal) {
+        let modes = terminal.track_modes();
+        *self.loaded.borrow_mut() = Some(Loaded { terminal, modes });
+    }
+}
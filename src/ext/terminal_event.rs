@@ -0,0 +1,197 @@
+//! A single subscription for all of a [`Terminal`]'s events.
+//!
+//! Rather than wiring up a listener per event (see the [`event`] module),
+//! [`Terminal::events`] (or [`Terminal::events_of`] for a subset of the events)
+//! returns one [`EventStream`] that yields [`TerminalEvent`]s. Dropping the
+//! stream tears down every listener it registered.
+//!
+//! [`event`]: super::event
+
+use super::{EventStream, Terminal};
+use crate::xterm::{
+    Buffer, KeyEventData, RenderEventData, ResizeEventData, Str,
+};
+
+use core::any::Any;
+use std::rc::Rc;
+
+/// An event emitted by a [`Terminal`].
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone)]
+pub enum TerminalEvent {
+    /// Data from the terminal (i.e. user input); see [`Terminal::on_data`].
+    Data(Str),
+    /// Binary data from the terminal (i.e. certain mouse reports); see
+    /// [`Terminal::on_binary`].
+    Binary(Str),
+    /// A key was pressed; see [`Terminal::on_key`].
+    Key(KeyEventData),
+    /// The terminal was resized; see [`Terminal::on_resize`].
+    Resize(ResizeEventData),
+    /// The terminal scrolled to the given line; see [`Terminal::on_scroll`].
+    Scroll(u32),
+    /// Rows were rendered; see [`Terminal::on_render`].
+    Render(RenderEventData),
+    /// The title was changed; see [`Terminal::on_title_change`].
+    TitleChange(Str),
+    /// The selection changed; see [`Terminal::on_selection_change`].
+    SelectionChange,
+    /// The cursor moved; see [`Terminal::on_cursor_move`].
+    CursorMove,
+    /// A line feed was processed; see [`Terminal::on_line_feed`].
+    LineFeed,
+    /// The active buffer changed; see
+    /// [`BufferNamespace::on_buffer_change`].
+    ///
+    /// [`BufferNamespace::on_buffer_change`]: crate::xterm::BufferNamespace::on_buffer_change
+    BufferChange(Buffer),
+}
+
+/// The different kinds of [`TerminalEvent`]s, for picking which events to
+/// subscribe to with [`Terminal::events_of`].
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TerminalEventKind {
+    /// [`TerminalEvent::Data`].
+    Data,
+    /// [`TerminalEvent::Binary`].
+    Binary,
+    /// [`TerminalEvent::Key`].
+    Key,
+    /// [`TerminalEvent::Resize`].
+    Resize,
+    /// [`TerminalEvent::Scroll`].
+    Scroll,
+    /// [`TerminalEvent::Render`].
+    Render,
+    /// [`TerminalEvent::TitleChange`].
+    TitleChange,
+    /// [`TerminalEvent::SelectionChange`].
+    SelectionChange,
+    /// [`TerminalEvent::CursorMove`].
+    CursorMove,
+    /// [`TerminalEvent::LineFeed`].
+    LineFeed,
+    /// [`TerminalEvent::BufferChange`].
+    BufferChange,
+}
+
+impl TerminalEventKind {
+    /// Every kind of event.
+    pub const ALL: &'static [Self] = &[
+        Self::Data,
+        Self::Binary,
+        Self::Key,
+        Self::Resize,
+        Self::Scroll,
+        Self::Render,
+        Self::TitleChange,
+        Self::SelectionChange,
+        Self::CursorMove,
+        Self::LineFeed,
+        Self::BufferChange,
+    ];
+}
+
+impl TerminalEvent {
+    /// The kind of this event.
+    #[must_use]
+    pub fn kind(&self) -> TerminalEventKind {
+        use TerminalEventKind as K;
+
+        match self {
+            Self::Data(_) => K::Data,
+            Self::Binary(_) => K::Binary,
+            Self::Key(_) => K::Key,
+            Self::Resize(_) => K::Resize,
+            Self::Scroll(_) => K::Scroll,
+            Self::Render(_) => K::Render,
+            Self::TitleChange(_) => K::TitleChange,
+            Self::SelectionChange => K::SelectionChange,
+            Self::CursorMove => K::CursorMove,
+            Self::LineFeed => K::LineFeed,
+            Self::BufferChange(_) => K::BufferChange,
+        }
+    }
+}
+
+impl Terminal {
+    /// Subscribes to all of the terminal's events.
+    ///
+    /// See [`Terminal::events_of`].
+    #[must_use]
+    pub fn events(&self) -> EventStream<TerminalEvent> {
+        self.events_of(TerminalEventKind::ALL)
+    }
+
+    /// Subscribes to the given kinds of events, returning a stream of them.
+    ///
+    /// Only the listeners for the requested events are registered; dropping
+    /// the stream unregisters all of them (and frees their closures).
+    #[must_use]
+    pub fn events_of(
+        &self,
+        kinds: &[TerminalEventKind],
+    ) -> EventStream<TerminalEvent> {
+        use TerminalEvent as E;
+
+        EventStream::new(|push: Rc<dyn Fn(TerminalEvent)>| {
+            let mut listeners: Vec<Box<dyn Any>> = Vec::new();
+
+            let mut seen = Vec::with_capacity(kinds.len());
+            for kind in kinds {
+                if seen.contains(kind) {
+                    continue;
+                }
+                seen.push(*kind);
+
+                let push = push.clone();
+                let listener: Box<dyn Any> = match kind {
+                    TerminalEventKind::Data => {
+                        Box::new(self.data_listener(move |d| push(E::Data(d))))
+                    }
+                    TerminalEventKind::Binary => Box::new(
+                        self.binary_listener(move |b| push(E::Binary(b))),
+                    ),
+                    TerminalEventKind::Key => {
+                        Box::new(self.key_listener(move |k| push(E::Key(k))))
+                    }
+                    TerminalEventKind::Resize => Box::new(
+                        self.resize_listener(move |r| push(E::Resize(r))),
+                    ),
+                    TerminalEventKind::Scroll => Box::new(
+                        self.scroll_listener(move |l| push(E::Scroll(l))),
+                    ),
+                    TerminalEventKind::Render => Box::new(
+                        self.render_listener(move |r| push(E::Render(r))),
+                    ),
+                    TerminalEventKind::TitleChange => {
+                        Box::new(self.title_change_listener(move |t| {
+                            push(E::TitleChange(t));
+                        }))
+                    }
+                    TerminalEventKind::SelectionChange => {
+                        Box::new(self.selection_change_listener(move || {
+                            push(E::SelectionChange);
+                        }))
+                    }
+                    TerminalEventKind::CursorMove => Box::new(
+                        self.cursor_move_listener(move || push(E::CursorMove)),
+                    ),
+                    TerminalEventKind::LineFeed => Box::new(
+                        self.line_feed_listener(move || push(E::LineFeed)),
+                    ),
+                    TerminalEventKind::BufferChange => {
+                        Box::new(self.buffer().buffer_change_listener(
+                            move |b| push(E::BufferChange(b)),
+                        ))
+                    }
+                };
+
+                listeners.push(listener);
+            }
+
+            listeners
+        })
+    }
+}
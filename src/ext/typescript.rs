@@ -0,0 +1,504 @@
+//! TypeScript declarations for interfaces that are implemented in Rust.
+//!
+//! Every trait made with [`interface!`] gets a `ts_declaration` function that
+//! describes the JS side of the trait as a TypeScript `interface`. These can be
+//! gathered into a [`TsDeclarations`] and written out as a `.d.ts` file (from a
+//! build script, a test, or a small binary) for the JS code that ends up
+//! calling into a Rust implementation:
+//!
+//! ```rust,no_run
+//! use xterm_js_sys::ext::{TsDeclarations, XtermAddon};
+//! use xterm_js_sys::xterm::TerminalAddon;
+//!
+//! let mut decls = TsDeclarations::new();
+//! let _ = decls.add(<TerminalAddon as XtermAddon>::ts_declaration());
+//!
+//! std::fs::write("addon.d.ts", decls.to_string()).unwrap();
+//! ```
+//!
+//! Argument and return types are mapped to their TypeScript equivalents with
+//! [`ts_type`]; see its docs for the details.
+//!
+//! [`interface!`]: crate::interface
+
+use core::fmt::{self, Display};
+use core::iter::Peekable;
+use std::collections::BTreeSet;
+
+/// The `xterm` interfaces that have bindings in [`crate::xterm`], along with
+/// the names they go by in `xterm.d.ts`.
+const XTERM_INTERFACES: &[(&str, &str)] = &[
+    ("Buffer", "IBuffer"),
+    ("BufferCell", "IBufferCell"),
+    ("BufferCellPosition", "IBufferCellPosition"),
+    ("BufferLine", "IBufferLine"),
+    ("BufferNamespace", "IBufferNamespace"),
+    ("BufferRange", "IBufferRange"),
+    ("Disposable", "IDisposable"),
+    ("Link", "ILink"),
+    ("LinkProvider", "ILinkProvider"),
+    ("LocalizableStrings", "ILocalizableStrings"),
+    ("Marker", "IMarker"),
+    ("Parser", "IParser"),
+    ("SelectionPosition", "ISelectionPosition"),
+    ("Terminal", "Terminal"),
+    ("TerminalAddon", "ITerminalAddon"),
+    ("UnicodeHandling", "IUnicodeHandling"),
+    ("UnicodeVersionProvider", "IUnicodeVersionProvider"),
+];
+
+/// Event payloads that `xterm.d.ts` spells out inline rather than naming.
+const XTERM_EVENT_DATA: &[(&str, &str)] = &[
+    ("KeyEventData", "{ key: string, domEvent: KeyboardEvent }"),
+    ("RenderEventData", "{ start: number, end: number }"),
+    ("ResizeEventData", "{ cols: number, rows: number }"),
+];
+
+/// Rust element types that `wasm-bindgen` passes as typed arrays when they're
+/// in a `Vec` or a boxed slice.
+const TYPED_ARRAYS: &[(&str, &str)] = &[
+    ("u8", "Uint8Array"),
+    ("i8", "Int8Array"),
+    ("u16", "Uint16Array"),
+    ("i16", "Int16Array"),
+    ("u32", "Uint32Array"),
+    ("i32", "Int32Array"),
+    ("u64", "BigUint64Array"),
+    ("i64", "BigInt64Array"),
+    ("f32", "Float32Array"),
+    ("f64", "Float64Array"),
+];
+
+/// Maps a Rust type (as written, i.e. with `stringify!`) to the TypeScript
+/// type that `wasm-bindgen` hands JS code for it.
+///
+/// In particular:
+///   - numbers become `number` (or `bigint` for the 64 and 128 bit ones)
+///   - `bool` becomes `boolean` and `char`, `str`, `String` and [`Str`]
+///     become `string`
+///   - `()` becomes `void` and [`JsValue`] becomes `any`
+///   - `Option<T>` becomes `T | undefined` and `Result<T, E>` becomes `T`
+///     (`wasm-bindgen` throws the error)
+///   - `Vec<T>`, `Box<[T]>` and slices become arrays (typed arrays for
+///     numbers)
+///   - references and `Box`es are looked through
+///   - the `xterm` types in [`crate::xterm`] get their `xterm.d.ts` names (i.e.
+///     [`BufferRange`] becomes `IBufferRange`)
+///   - `web-sys`'s `Html*` types get their DOM names (i.e. `HtmlElement`
+///     becomes `HTMLElement`)
+///
+/// Anything else is assumed to be a JS type with the same name as its Rust
+/// binding (paths are dropped). Types that can't be understood at all (trait
+/// objects, function pointers) become `any`.
+///
+/// [`Str`]: crate::xterm::Str
+/// [`JsValue`]: wasm_bindgen::JsValue
+/// [`BufferRange`]: crate::xterm::BufferRange
+#[must_use]
+pub fn ts_type(rust: &str) -> String {
+    let mut tokens = tokenize(rust).into_iter().peekable();
+
+    match parse(&mut tokens) {
+        Some(ty) if tokens.next().is_none() => ty.to_ts(),
+        _ => "any".to_string(),
+    }
+}
+
+/// A bare bones type, as parsed from a `stringify!`-ed Rust type.
+#[derive(Debug)]
+enum Type {
+    /// A path; just the last segment (and its generic arguments) is kept.
+    Path(String, Vec<Type>),
+    /// A tuple; `()` is the unit type.
+    Tuple(Vec<Type>),
+    /// A slice or an array.
+    Slice(Box<Type>),
+}
+
+impl Type {
+    /// The TypeScript equivalent of this type (see [`ts_type`]).
+    fn to_ts(&self) -> String {
+        match self {
+            Type::Tuple(tys) if tys.is_empty() => "void".to_string(),
+            Type::Tuple(_) => "any".to_string(),
+            Type::Slice(elem) => elem.array_of(),
+            Type::Path(name, args) if args.is_empty() => {
+                Self::named_to_ts(name)
+            }
+            Type::Path(name, args) => match (name.as_str(), args.as_slice()) {
+                ("Option", [inner]) => format!("{} | undefined", inner.to_ts()),
+                // `wasm-bindgen` throws the error.
+                ("Result", [ok, _]) => ok.to_ts(),
+                ("Box", [inner]) => inner.to_ts(),
+                ("Vec", [elem]) => elem.array_of(),
+                _ => "any".to_string(),
+            },
+        }
+    }
+
+    /// The TypeScript equivalent of a type without generic arguments.
+    fn named_to_ts(name: &str) -> String {
+        match name {
+            "u8" | "u16" | "u32" | "usize" | "i8" | "i16" | "i32" | "isize"
+            | "f32" | "f64" => "number".to_string(),
+            "u64" | "u128" | "i64" | "i128" => "bigint".to_string(),
+            "bool" => "boolean".to_string(),
+            "char" | "str" | "String" | "Str" | "JsString" => {
+                "string".to_string()
+            }
+            "JsValue" => "any".to_string(),
+            "Object" => "object".to_string(),
+            "Array" => "any[]".to_string(),
+            "Promise" => "Promise<any>".to_string(),
+            name => {
+                if let Some((_, ts)) = XTERM_INTERFACES
+                    .iter()
+                    .chain(XTERM_EVENT_DATA)
+                    .find(|(rust, _)| *rust == name)
+                {
+                    (*ts).to_string()
+                } else if name.starts_with("Html") {
+                    name.replacen("Html", "HTML", 1)
+                } else {
+                    name.to_string()
+                }
+            }
+        }
+    }
+
+    /// The TypeScript type for an array of this type.
+    fn array_of(&self) -> String {
+        if let Type::Path(name, args) = self {
+            if let Some((_, ts)) = TYPED_ARRAYS
+                .iter()
+                .find(|(rust, _)| args.is_empty() && *rust == name.as_str())
+            {
+                return (*ts).to_string();
+            }
+        }
+
+        let elem = self.to_ts();
+        if elem.contains(' ') {
+            format!("({})[]", elem)
+        } else {
+            format!("{}[]", elem)
+        }
+    }
+}
+
+/// Splits a `stringify!`-ed type into identifiers and punctuation.
+///
+/// Lifetimes are dropped, as are the `dyn` and `mut` keywords.
+fn tokenize(rust: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = rust.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c.is_alphanumeric() || c == '_' || c == '\'' {
+            let mut word = c.to_string();
+            while let Some(&c) = chars.peek() {
+                if c.is_alphanumeric() || c == '_' {
+                    word.push(c);
+                    let _ = chars.next();
+                } else {
+                    break;
+                }
+            }
+
+            if !(word.starts_with('\'') || word == "dyn" || word == "mut") {
+                tokens.push(word);
+            }
+        } else if c == ':' && chars.peek() == Some(&':') {
+            let _ = chars.next();
+            tokens.push("::".to_string());
+        } else if !c.is_whitespace() {
+            tokens.push(c.to_string());
+        }
+    }
+
+    tokens
+}
+
+/// Parses one type from `tokens`.
+fn parse<I: Iterator<Item = String>>(tokens: &mut Peekable<I>) -> Option<Type> {
+    match tokens.next()?.as_str() {
+        "&" => {
+            // Lifetimes and `mut` were already dropped.
+            parse(tokens)
+        }
+        "(" => {
+            let tys = parse_list(tokens, ")")?;
+            Some(Type::Tuple(tys))
+        }
+        "[" => {
+            let elem = parse(tokens)?;
+            // Arrays (`[T; N]`) and slices are the same to JS.
+            for token in tokens {
+                if token == "]" {
+                    return Some(Type::Slice(Box::new(elem)));
+                }
+            }
+            None
+        }
+        "::" => parse(tokens),
+        segment => {
+            let mut name = segment.to_string();
+            loop {
+                match tokens.peek().map(String::as_str) {
+                    Some("::") => {
+                        let _ = tokens.next();
+                        if tokens.peek().map(String::as_str) == Some("<") {
+                            continue;
+                        }
+                        name = tokens.next()?;
+                    }
+                    Some("<") => {
+                        let _ = tokens.next();
+                        let args = parse_list(tokens, ">")?;
+                        return Some(Type::Path(name, args));
+                    }
+                    _ => return Some(Type::Path(name, Vec::new())),
+                }
+            }
+        }
+    }
+}
+
+/// Parses a comma separated list of types up to (and including) `close`.
+fn parse_list<I: Iterator<Item = String>>(
+    tokens: &mut Peekable<I>,
+    close: &str,
+) -> Option<Vec<Type>> {
+    let mut tys = Vec::new();
+
+    loop {
+        if tokens.peek().map(String::as_str) == Some(close) {
+            let _ = tokens.next();
+            return Some(tys);
+        }
+
+        tys.push(parse(tokens)?);
+
+        match tokens.next()?.as_str() {
+            "," => {}
+            c if c == close => return Some(tys),
+            _ => return None,
+        }
+    }
+}
+
+/// The different kinds of members an [`interface!`] trait can have.
+///
+/// [`interface!`]: crate::interface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[allow(clippy::module_name_repetitions)]
+pub enum TsMemberKind {
+    /// A regular method.
+    Method,
+    /// A property (i.e. `readonly name: T;`).
+    Getter,
+    /// A method that JS objects don't have to have.
+    Optional,
+    /// A method that returns a `Promise`.
+    Promise,
+}
+
+/// A member of a TypeScript interface; produced by [`interface!`].
+///
+/// [`interface!`]: crate::interface
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(clippy::module_name_repetitions)]
+pub struct TsMember {
+    /// What sort of member this is.
+    kind: TsMemberKind,
+    /// The member's name.
+    name: String,
+    /// The member's arguments: names and TypeScript types.
+    args: Vec<(String, String)>,
+    /// The member's TypeScript return type.
+    ret: String,
+}
+
+impl TsMember {
+    /// Makes a member from the Rust types of its arguments and return value
+    /// (an empty return type is `()`).
+    ///
+    /// The types are mapped with [`ts_type`]. For [`TsMemberKind::Promise`]
+    /// members, `ret` is the type the `Promise` resolves to.
+    #[must_use]
+    pub fn new(
+        kind: TsMemberKind,
+        name: &str,
+        args: &[(&str, &str)],
+        ret: &str,
+    ) -> Self {
+        let ret = if ret.is_empty() {
+            "void".to_string()
+        } else {
+            ts_type(ret)
+        };
+
+        Self {
+            kind,
+            name: name.to_string(),
+            args: args
+                .iter()
+                .map(|(name, ty)| ((*name).to_string(), ts_type(ty)))
+                .collect(),
+            ret: if kind == TsMemberKind::Promise {
+                format!("Promise<{}>", ret)
+            } else {
+                ret
+            },
+        }
+    }
+}
+
+impl Display for TsMember {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.kind == TsMemberKind::Getter {
+            return write!(f, "readonly {}: {};", self.name, self.ret);
+        }
+
+        write!(f, "{}", self.name)?;
+        if self.kind == TsMemberKind::Optional {
+            write!(f, "?")?;
+        }
+
+        write!(f, "(")?;
+        for (idx, (name, ty)) in self.args.iter().enumerate() {
+            if idx != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}: {}", name, ty)?;
+        }
+        write!(f, "): {};", self.ret)
+    }
+}
+
+/// A TypeScript interface declaration for an [`interface!`] trait.
+///
+/// These are made by the `ts_declaration` function that [`interface!`] adds to
+/// the traits it makes and are printed (with [`Display`]) as an exported
+/// TypeScript `interface`.
+///
+/// [`interface!`]: crate::interface
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(clippy::module_name_repetitions)]
+pub struct TsDeclaration {
+    /// The TypeScript name of the interface.
+    name: String,
+    /// The TypeScript names of the interfaces this one extends.
+    extends: Vec<String>,
+    /// The interface's members.
+    members: Vec<TsMember>,
+}
+
+impl TsDeclaration {
+    /// Starts a declaration for the JS interface with the given Rust name
+    /// (mapped with [`ts_type`]).
+    #[must_use]
+    pub fn new(js_interface: &str) -> Self {
+        Self {
+            name: ts_type(js_interface),
+            extends: Vec::new(),
+            members: Vec::new(),
+        }
+    }
+
+    /// Adds an interface (by its Rust name) that this one extends.
+    #[must_use]
+    pub fn extends(mut self, js_interface: &str) -> Self {
+        self.extends.push(ts_type(js_interface));
+        self
+    }
+
+    /// Adds a member.
+    #[must_use]
+    pub fn member(mut self, member: TsMember) -> Self {
+        self.members.push(member);
+        self
+    }
+
+    /// The TypeScript name of the interface.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Display for TsDeclaration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "export interface {}", self.name)?;
+        if !self.extends.is_empty() {
+            write!(f, " extends {}", self.extends.join(", "))?;
+        }
+        writeln!(f, " {{")?;
+
+        for member in &self.members {
+            writeln!(f, "    {}", member)?;
+        }
+
+        writeln!(f, "}}")
+    }
+}
+
+/// A set of [`TsDeclaration`]s that makes up a `.d.ts` file.
+///
+/// When printed (with [`Display`]), the declarations are preceded by an import
+/// for the `xterm` types they use.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[allow(clippy::module_name_repetitions)]
+pub struct TsDeclarations {
+    /// The declarations, in the order they were added.
+    decls: Vec<TsDeclaration>,
+}
+
+impl TsDeclarations {
+    /// An empty set of declarations.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a declaration.
+    pub fn add(&mut self, decl: TsDeclaration) -> &mut Self {
+        self.decls.push(decl);
+        self
+    }
+}
+
+impl Display for TsDeclarations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rendered: Vec<String> =
+            self.decls.iter().map(ToString::to_string).collect();
+
+        // Anything from `xterm` that's used but not declared here needs to be
+        // imported.
+        let imports: BTreeSet<&str> = rendered
+            .iter()
+            .flat_map(|decl| {
+                decl.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            })
+            .filter(|word| {
+                XTERM_INTERFACES.iter().any(|(_, ts)| ts == word)
+                    && self.decls.iter().all(|decl| decl.name != *word)
+            })
+            .collect();
+
+        if !imports.is_empty() {
+            let imports: Vec<_> = imports.into_iter().collect();
+            writeln!(f, "import {{ {} }} from 'xterm';", imports.join(", "))?;
+            writeln!(f)?;
+        }
+
+        for (idx, decl) in rendered.iter().enumerate() {
+            if idx != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", decl)?;
+        }
+
+        Ok(())
+    }
+}
@@ -3,7 +3,7 @@
 //! [`UnicodeHandling`]: crate::xterm::UnicodeHandling
 //! [`UnicodeVersionProvider`]: crate::xterm::UnicodeVersionProvider
 
-use super::{interface, IntoJsInterface};
+use super::{interface, InterfaceHandle, IntoJsInterface};
 use crate::xterm::{
     Str, UnicodeHandling, UnicodeVersionProvider, WideCharacterWidth,
 };
@@ -11,7 +11,7 @@ use crate::xterm::{
 interface! {
     pub trait XtermUnicodeVersionProvider mirrors UnicodeVersionProvider {
         /// Gets a string indicating the Unicode version provided.
-        fn version(&self) -> Str;
+        readonly version: Str;
 
         /// Unicode version dependent `wcwidth` implementation.
         fn wcwidth(&self, codepoint: u32) -> WideCharacterWidth;
@@ -25,11 +25,38 @@ impl UnicodeHandling {
     /// well as [JS defined providers](UnicodeVersionProvider) as is otherwise
     /// identical to [`UnicodeHandling::register`].
     ///
+    /// xterm.js has no way to unregister providers so Rust providers live for
+    /// as long as the page does; see [`register_version_provider_handle`] if
+    /// you need to free one.
+    ///
     /// [custom Unicode version provider]: XtermUnicodeVersionProvider
+    /// [`register_version_provider_handle`]: UnicodeHandling::register_version_provider_handle
     pub fn register_version_provider<P>(&self, provider: &P)
     where
         P: IntoJsInterface<UnicodeVersionProvider>,
     {
         self.register(provider.by_ref())
     }
+
+    /// Registers a [Rust defined Unicode version provider] and returns the
+    /// [`InterfaceHandle`] that owns it.
+    ///
+    /// xterm.js has no way to unregister providers, so the handle has to be
+    /// kept around for as long as the terminal is: dropping it frees the
+    /// provider, after which xterm.js's calls to it throw.
+    ///
+    /// [Rust defined Unicode version provider]: XtermUnicodeVersionProvider
+    #[must_use]
+    pub fn register_version_provider_handle<P>(
+        &self,
+        provider: P,
+    ) -> InterfaceHandle<UnicodeVersionProvider>
+    where
+        P: XtermUnicodeVersionProvider + 'static,
+    {
+        let handle = provider.into_js_handle();
+        self.register(handle.object().clone());
+
+        handle
+    }
 }
@@ -0,0 +1,165 @@
+//! Helpers for writing bytes to the terminal and for waiting on writes.
+
+use crate::xterm::Terminal;
+
+use js_sys::{Function, Uint8Array};
+use wasm_bindgen::{prelude::*, JsCast};
+
+use core::cell::RefCell;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use std::rc::Rc;
+
+// Note: we can't just hand xterm.js a `&[u8]`; wasm-bindgen passes slices to JS
+// as views into wasm memory and xterm.js holds on to the data it's given until
+// it gets around to parsing it (by which point the view may point to something
+// else entirely or may have been detached by a memory grow). So, we copy.
+
+impl Terminal {
+    /// Writes raw (UTF-8 encoded) bytes to the terminal.
+    ///
+    /// Unlike [`write`], the data does not need to be valid UTF-8 (or be
+    /// turned into a `String`); xterm.js decodes it (and handles sequences that
+    /// are split across writes).
+    ///
+    /// The bytes are copied into a JS `Uint8Array`; see
+    /// [`write_uint8_array`] to write an existing array.
+    ///
+    /// [`write`]: Terminal::write
+    /// [`write_uint8_array`]: Terminal::write_uint8_array
+    pub fn write_bytes(&self, data: &[u8]) {
+        self.write_uint8_array(&Uint8Array::from(data));
+    }
+
+    /// Writes raw (UTF-8 encoded) bytes to the terminal and takes a callback
+    /// that fires when the data was processed by the parser.
+    ///
+    /// See [`write_bytes`].
+    ///
+    /// [`write_bytes`]: Terminal::write_bytes
+    pub fn write_bytes_with_callback(
+        &self,
+        data: &[u8],
+        callback: &Closure<dyn FnMut()>,
+    ) {
+        self.write_uint8_array_with_callback(&Uint8Array::from(data), callback);
+    }
+
+    /// Writes raw (UTF-8 encoded) bytes to the terminal, followed by a break
+    /// line character (`\n`).
+    ///
+    /// See [`write_bytes`].
+    ///
+    /// [`write_bytes`]: Terminal::write_bytes
+    pub fn writeln_bytes(&self, data: &[u8]) {
+        self.writeln_uint8_array(&Uint8Array::from(data));
+    }
+}
+
+#[wasm_bindgen]
+extern "C" {
+    /// [`Terminal::write_with_callback`], but with a JS function for the
+    /// callback (and a JS value for the data so it can be either a string or a
+    /// `Uint8Array`).
+    #[wasm_bindgen(method, js_name = write)]
+    pub(super) fn write_with_function(
+        this: &Terminal,
+        data: &JsValue,
+        callback: &Function,
+    );
+}
+
+/// State shared between a [`WriteFuture`] and the write callback.
+#[derive(Debug, Default)]
+struct WriteState {
+    /// Whether the write callback has fired.
+    done: bool,
+    /// The waker of the task that last polled the future.
+    waker: Option<Waker>,
+}
+
+/// A [`Future`] that resolves once xterm.js has processed a write.
+///
+/// See [`Terminal::write_async`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct WriteFuture {
+    /// Shared with the write callback.
+    state: Rc<RefCell<WriteState>>,
+}
+
+impl WriteFuture {
+    /// Writes `data` (a string or a `Uint8Array`) and returns a future for
+    /// the write.
+    fn new(term: &Terminal, data: &JsValue) -> Self {
+        let state = Rc::new(RefCell::new(WriteState::default()));
+
+        // `once_into_js` frees the closure after it's called; xterm.js calls
+        // write callbacks exactly once.
+        let callback = Closure::once_into_js({
+            let state = state.clone();
+            move || {
+                let waker = {
+                    let mut state = state.borrow_mut();
+                    state.done = true;
+                    state.waker.take()
+                };
+
+                if let Some(waker) = waker {
+                    waker.wake();
+                }
+            }
+        });
+
+        term.write_with_function(data, callback.unchecked_ref());
+        Self { state }
+    }
+}
+
+impl Future for WriteFuture {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.state.borrow_mut();
+
+        if state.done {
+            Poll::Ready(())
+        } else {
+            state.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+impl Terminal {
+    /// Writes data to the terminal, returning a [`Future`] that resolves once
+    /// the data has been processed by the parser.
+    ///
+    /// Unlike [`write_with_callback`] this doesn't require a (leaked)
+    /// [`Closure`]; the callback that's handed to xterm.js is freed once it's
+    /// called.
+    ///
+    /// Note that the write happens when this function is called and not when
+    /// the returned future is first polled, so writes are always issued in
+    /// order. Once the future resolves the [`Buffer`] reflects the write (i.e.
+    /// the cursor position can be queried).
+    ///
+    /// [`write_with_callback`]: Terminal::write_with_callback
+    /// [`Buffer`]: crate::xterm::Buffer
+    pub fn write_async(&self, data: &str) -> WriteFuture {
+        WriteFuture::new(self, &JsValue::from_str(data))
+    }
+
+    /// Writes raw (UTF-8 encoded) bytes to the terminal, returning a
+    /// [`Future`] that resolves once the data has been processed by the
+    /// parser.
+    ///
+    /// See [`write_async`] and [`write_bytes`].
+    ///
+    /// [`write_async`]: Terminal::write_async
+    /// [`write_bytes`]: Terminal::write_bytes
+    pub fn write_bytes_async(&self, data: &[u8]) -> WriteFuture {
+        WriteFuture::new(self, &Uint8Array::from(data))
+    }
+}
@@ -158,12 +158,25 @@
 //!           we do preform the leaking (we don't want to require a `'static`
 //!           reference for JS impls which would require users to leak them
 //!           unnecessarily).
+//!         * Update (again): leaking is no longer necessary. The conversion
+//!           moves the Rust impl into an `Rc` that the closures share and
+//!           hands back an [`InterfaceHandle`] that owns all of it; the
+//!           handle frees everything when it's dropped or when the JS
+//!           object's `dispose` method is called.
 //!      + Traits that take a mutable reference to self **and** have more than
 //!        one method aren't possible (safely) because the closures we pass
 //!        along hold a reference to the actual instance and we can't have
 //!        mutability with aliasing. So, we'll just make all trait methods only
 //!        take an immutable reference to self (as `wasm-bindgen` does). Rust
 //!        trait implementors will need to use interior mutability.
+//!         * Update: the conversion now keeps the Rust impl in a `RefCell`
+//!           so `&mut self` methods are supported; re-entrant calls into a
+//!           `&mut self` method throw instead of aliasing.
+//!   - Interfaces also have properties (i.e. `version` on
+//!     [`UnicodeVersionProvider`]). An entry that points to a Rust function
+//!     won't do for these since JS code reads them rather than calling them,
+//!     so the Rust trait gets a method for each property and the entry is
+//!     defined as a getter that calls it.
 //!
 //! The final piece required is an extension method that takes the Rust trait
 //! impl instead of the concrete type and then converts it to concrete type
@@ -177,6 +190,7 @@
 //! [`JsCast::instanceof`]: wasm_bindgen::JsCast::instanceof
 //!
 //! [`XtermAddon`]: super::ext::addon::XtermAddon
+//! [`InterfaceHandle`]: super::ext::InterfaceHandle
 //!
 //! [`Deref`]: core::ops::Deref
 //! [`AsRef`]: core::convert::AsRef
@@ -1131,6 +1145,7 @@ extern "C" {
     /// (This is a [duck-typed interface]).
     ///
     /// [duck-typed interface]: https://rustwasm.github.io/docs/wasm-bindgen/reference/working-with-duck-typed-interfaces.html
+    #[derive(Debug, Clone)]
     pub type BufferCell;
 
     /// Gets a cell’s background color number, this differs depending on what
@@ -1267,6 +1282,7 @@ extern "C" {
     /// (This is a [duck-typed interface]).
     ///
     /// [duck-typed interface]: https://rustwasm.github.io/docs/wasm-bindgen/reference/working-with-duck-typed-interfaces.html
+    #[derive(Debug, Clone)]
     pub type BufferLine;
 
     /// Whether the line is wrapped from the previous line.
@@ -1298,6 +1314,7 @@ extern "C" {
     #[wasm_bindgen(structural, method, js_name = getCell)]
     pub fn get_cell(
         this: &BufferLine,
+        x: u16,
         cell: Option<BufferCell>,
     ) -> Option<BufferCell>;
 
@@ -1425,6 +1442,48 @@ extern "C" {
     ) -> Disposable;
 }
 
+#[wasm_bindgen(module = "xterm")]
+extern "C" {
+    /// A position within a buffer.
+    ///
+    /// (This is a [duck-typed interface]; instances can be made with
+    /// [`BufferCellPosition::new`] when the `ext` feature is enabled).
+    ///
+    /// [duck-typed interface]: https://rustwasm.github.io/docs/wasm-bindgen/reference/working-with-duck-typed-interfaces.html
+    /// [`BufferCellPosition::new`]: BufferCellPosition::new
+    #[derive(Debug, Clone)]
+    pub type BufferCellPosition;
+
+    /// The x position within the buffer (1-based).
+    #[wasm_bindgen(structural, method, getter = x)]
+    pub fn x(this: &BufferCellPosition) -> u16;
+
+    /// The y position within the buffer (1-based).
+    #[wasm_bindgen(structural, method, getter = y)]
+    pub fn y(this: &BufferCellPosition) -> u32;
+}
+
+#[wasm_bindgen(module = "xterm")]
+extern "C" {
+    /// A range within a buffer.
+    ///
+    /// (This is a [duck-typed interface]; instances can be made with
+    /// [`BufferRange::new`] when the `ext` feature is enabled).
+    ///
+    /// [duck-typed interface]: https://rustwasm.github.io/docs/wasm-bindgen/reference/working-with-duck-typed-interfaces.html
+    /// [`BufferRange::new`]: BufferRange::new
+    #[derive(Debug, Clone)]
+    pub type BufferRange;
+
+    /// The start position of the range.
+    #[wasm_bindgen(structural, method, getter = start)]
+    pub fn start(this: &BufferRange) -> BufferCellPosition;
+
+    /// The end position of the range (inclusive).
+    #[wasm_bindgen(structural, method, getter = end)]
+    pub fn end(this: &BufferRange) -> BufferCellPosition;
+}
+
 #[wasm_bindgen(module = "xterm")]
 extern "C" {
     /// An object that can be disposed via a dispose function.
@@ -1446,6 +1505,86 @@ extern "C" {
     pub fn dispose(this: &Disposable);
 }
 
+#[wasm_bindgen(module = "xterm")]
+extern "C" {
+    /// A link within the terminal, as handed to xterm.js by a
+    /// [`LinkProvider`].
+    ///
+    /// (This is a [duck-typed interface]).
+    ///
+    /// [duck-typed interface]: https://rustwasm.github.io/docs/wasm-bindgen/reference/working-with-duck-typed-interfaces.html
+    #[derive(Debug, Clone)]
+    pub type Link;
+
+    /// The buffer range of the link.
+    #[wasm_bindgen(structural, method, getter = range)]
+    pub fn range(this: &Link) -> BufferRange;
+
+    /// The text of the link.
+    #[wasm_bindgen(structural, method, getter = text)]
+    pub fn text(this: &Link) -> Str;
+
+    /// Called when the link is activated.
+    ///
+    /// Takes:
+    ///   - `event`: The mouse event triggering the callback.
+    ///   - `text`:  The text of the link.
+    #[wasm_bindgen(structural, method, js_name = activate)]
+    pub fn activate(this: &Link, event: web_sys::MouseEvent, text: Str);
+
+    /// Called when the mouse hovers over the link (optional).
+    ///
+    /// Takes:
+    ///   - `event`: The mouse event triggering the callback.
+    ///   - `text`:  The text of the link.
+    #[wasm_bindgen(structural, method, js_name = hover)]
+    pub fn hover(this: &Link, event: web_sys::MouseEvent, text: Str);
+
+    /// Called when the mouse leaves the link (optional).
+    ///
+    /// Takes:
+    ///   - `event`: The mouse event triggering the callback.
+    ///   - `text`:  The text of the link.
+    #[wasm_bindgen(structural, method, js_name = leave)]
+    pub fn leave(this: &Link, event: web_sys::MouseEvent, text: Str);
+}
+
+#[wasm_bindgen(module = "xterm")]
+extern "C" {
+    /// A provider that can provide links to the terminal.
+    ///
+    /// (This is a [duck-typed interface]; its Rust dual is available [here]
+    /// when the `ext` feature is enabled).
+    ///
+    /// [duck-typed interface]: https://rustwasm.github.io/docs/wasm-bindgen/reference/working-with-duck-typed-interfaces.html
+    /// [here]: crate::ext::link::XtermLinkProvider
+    #[derive(Debug, Clone)]
+    pub type LinkProvider;
+
+    /// Provides links for the buffer line.
+    ///
+    /// Takes:
+    ///   - `buffer_line_number`: The y position of the buffer line (1-based).
+    ///   - `callback`:           The callback to be fired when ready with the
+    ///                           resulting links (an array of [`Link`]s) or
+    ///                           `undefined` when there are none.
+    ///
+    /// Since the signature, post-translation, is rather cryptic, here's the
+    /// original TypeScript binding:
+    /// ```ts
+    /// provideLinks(
+    ///     bufferLineNumber: number,
+    ///     callback: (links: ILink[] | undefined) => void,
+    /// ): void;
+    /// ```
+    #[wasm_bindgen(structural, method, js_name = provideLinks)]
+    pub fn provide_links(
+        this: &LinkProvider,
+        buffer_line_number: u32,
+        callback: js_sys::Function,
+    );
+}
+
 #[wasm_bindgen(module = "xterm")]
 extern "C" {
     /// The set of localizable strings.
@@ -1501,6 +1640,21 @@ extern "C" {
     /// [`get_line`]: Marker::get_line
     #[wasm_bindgen(structural, method, getter = line)]
     pub fn line(this: &Marker) -> i32;
+
+    /// Adds an event listener for when the marker is disposed (i.e. when the
+    /// line it's on is trimmed from the scrollback).
+    ///
+    /// Returns a [`Disposable`] to stop listening.
+    ///
+    /// See [`attach_dispose_event_listener`] (if the `ext` feature is enabled)
+    /// for a friendlier version of this function.
+    ///
+    /// [`attach_dispose_event_listener`]: Marker::attach_dispose_event_listener
+    #[wasm_bindgen(structural, method, js_name = onDispose)]
+    pub fn on_dispose(
+        this: &Marker,
+        listener: &Closure<dyn FnMut()>,
+    ) -> Disposable;
 }
 
 #[wasm_bindgen(module = "xterm")]
@@ -1531,7 +1685,7 @@ extern "C" {
     ///
     /// [`register_csi_handler`]: Parser::register_csi_handler
     #[wasm_bindgen(structural, method, js_name = registerCsiHandler)]
-    fn register_csi_handler(
+    pub fn register_csi_handler(
         this: &Parser,
         id: FunctionIdentifier,
         // This can actually be given either a `ReadOnlyArray<u32>` or a
@@ -1565,7 +1719,7 @@ extern "C" {
     ///
     /// [`register_dcs_handler`]: Parser::register_dcs_handler
     #[wasm_bindgen(structural, method, js_name = registerDcsHandler)]
-    fn register_dcs_handler(
+    pub fn register_dcs_handler(
         this: &Parser,
         id: FunctionIdentifier,
         // Like `register_csi_handler`'s callback, this can either be given a
@@ -1590,7 +1744,7 @@ extern "C" {
     ///
     /// [`register_esc_handler`]: Parser::register_esc_handler
     #[wasm_bindgen(structural, method, js_name = registerEscHandler)]
-    fn register_esc_handler(
+    pub fn register_esc_handler(
         this: &Parser,
         id: FunctionIdentifier,
         handler: &Closure<dyn FnMut() -> bool>,
@@ -1618,7 +1772,7 @@ extern "C" {
     ///
     /// [`register_osc_handler`]: Parser::register_osc_handler
     #[wasm_bindgen(structural, method, js_name = registerOscHandler)]
-    fn register_osc_handler(
+    pub fn register_osc_handler(
         this: &Parser,
         ident: u32,
         callback: &Closure<dyn FnMut(Str) -> bool>,
@@ -2060,29 +2214,40 @@ extern "C" {
 
     ///////////////////////////////  Methods  ///////////////////////////////
 
-    /*  [TODO]
-        addMarker
-        ▸ addMarker(cursorYOffset: number): IMarker | undefined
-        deprecated use registerMarker instead.
-        Parameters:
-        Name    Type
-        cursorYOffset   number
-        Returns: IMarker | undefined
-    */
+    /// Adds a marker to the normal buffer and returns it. If the alt buffer is
+    /// active, `None` is returned.
+    ///
+    /// Takes:
+    ///   - `cursor_y_offset`: The y position offset of the marker from the
+    ///                        cursor.
+    #[deprecated(note = "Use `Terminal::register_marker` instead.")]
+    #[wasm_bindgen(method, js_name = addMarker)]
+    pub fn add_marker(this: &Terminal, cursor_y_offset: i32) -> Option<Marker>;
 
-    /*  [TODO]
-        attachCustomKeyEventHandler
-        ▸ attachCustomKeyEventHandler(customKeyEventHandler: function): void
-        Attaches a custom key event handler which is run before keys are processed, giving consumers of xterm.js ultimate control as to what keys should be processed by the terminal and what keys should not.
-        Parameters:
-        ▪ customKeyEventHandler: function
-        The custom KeyboardEvent handler to attach. This is a function that takes a KeyboardEvent, allowing consumers to stop propagation and/or prevent the default action. The function returns whether the event should be processed by xterm.js.
-        ▸ (event: KeyboardEvent): boolean
-        Parameters:
-        Name    Type
-        event   KeyboardEvent
-        Returns: void
-    */
+    /// Attaches a custom key event handler which is run before keys are
+    /// processed, giving consumers of xterm.js ultimate control as to what keys
+    /// should be processed by the terminal and what keys should not.
+    ///
+    /// Takes:
+    ///   - `custom_key_event_handler`: The custom [`KeyboardEvent`] handler to
+    ///                                 attach. This is a function that takes a
+    ///                                 [`KeyboardEvent`], allowing consumers to
+    ///                                 stop propagation and/or prevent the
+    ///                                 default action. The function returns
+    ///                                 whether the event should be processed
+    ///                                 by xterm.js.
+    ///
+    /// Only one handler can be attached at a time; attaching a handler replaces
+    /// the previous one.
+    ///
+    /// [`KeyboardEvent`]: web_sys::KeyboardEvent
+    #[wasm_bindgen(method, js_name = attachCustomKeyEventHandler)]
+    pub fn attach_custom_key_event_handler(
+        this: &Terminal,
+        custom_key_event_handler: &Closure<
+            dyn FnMut(web_sys::KeyboardEvent) -> bool,
+        >,
+    );
 
     /// Unfocus the terminal.
     #[wasm_bindgen(method, js_name = blur)]
@@ -2106,51 +2271,30 @@ extern "C" {
         Returns: void
     */
 
-    /*  [TODO]
-        deregisterLinkMatcher
-        ▸ deregisterLinkMatcher(matcherId: number): void
-        (EXPERIMENTAL) Deregisters a link matcher if it has been registered.
-        @deprecated The link matcher API is now deprecated in favor of the link provider API, see `registerLinkProvider`.
-        Parameters:
-        Name    Type    Description
-        matcherId   number  The link matcher’s ID (returned after register)
-        Returns: void
-    */
+    /// **[EXPERIMENTAL]** Deregisters a link matcher if it has been
+    /// registered.
+    ///
+    /// Takes:
+    ///   - `matcher_id`: The link matcher’s ID (returned after register).
+    #[deprecated(
+        since = "4.6.0",
+        note = "The link matcher API is deprecated in favor of the link \
+        provider API. See: `Terminal::register_link_provider`."
+    )]
+    #[wasm_bindgen(method, js_name = deregisterLinkMatcher)]
+    pub fn deregister_link_matcher(this: &Terminal, matcher_id: u32);
 
     /// Focus the terminal.
     #[wasm_bindgen(method, js_name = focus)]
     pub fn focus(this: &Terminal);
 
-    /*  [TODO]
-        getOption
-        ▸ getOption(key: “bellSound”    “bellStyle”     “cursorStyle”   “fontFamily”    “fontWeight”    “fontWeightBold”    “logLevel”  “rendererType”  “termName”  “wordSeparator”): string
-        Retrieves an option’s value from the terminal.
-        Parameters:
-        Name    Type    Description
-        key     “bellSound” | “bellStyle” | “cursorStyle” | “fontFamily” | “fontWeight” | “fontWeightBold” | “logLevel” | “rendererType” | “termName” | “wordSeparator”     The option key.
-        Returns: string
-
-        ▸ getOption(key: “allowTransparency”    “cancelEvents”  “convertEol”    “cursorBlink”   “disableStdin”  “macOptionIsMeta”   “rightClickSelectsWord”     “popOnBell”     “visualBell”    “windowsMode”): boolean
-        Retrieves an option’s value from the terminal.
-        Parameters:
-        Name    Type    Description
-        key     “allowTransparency” | “cancelEvents” | “convertEol” | “cursorBlink” | “disableStdin” | “macOptionIsMeta” | “rightClickSelectsWord” | “popOnBell” | “visualBell” | “windowsMode”     The option key.
-        Returns: boolean
-
-        ▸ getOption(key: “cols”     “fontSize”  “letterSpacing”     “lineHeight”    “rows”  “tabStopWidth”  “scrollback”): number
-        Retrieves an option’s value from the terminal.
-        Parameters:
-        Name    Type    Description
-        key     “cols” | “fontSize” | “letterSpacing” | “lineHeight” | “rows” | “tabStopWidth” | “scrollback”   The option key.
-        Returns: number
-
-        ▸ getOption(key: string): any
-        Retrieves an option’s value from the terminal.
-        Parameters:
-        Name    Type    Description
-        key     string  The option key.
-        Returns: any
-    */
+    /// Retrieves an option’s value from the terminal.
+    ///
+    /// Takes:
+    ///   - `key`: The option's name (i.e. `"fontSize"`); the names are the JS
+    ///            names of the fields of [`TerminalOptions`].
+    #[wasm_bindgen(method, js_name = getOption)]
+    pub fn get_option(this: &Terminal, key: &str) -> JsValue;
 
     /// Gets the terminal’s current selection; this is useful for implementing
     /// copy behavior outside of xterm.js.
@@ -2229,36 +2373,72 @@ extern "C" {
         The ID of the new joiner, this can be used to deregister
     */
 
-    /*  [TODO]
-        registerLinkMatcher
-        ▸ registerLinkMatcher(regex: RegExp, handler: function, options?: ILinkMatcherOptions): number
-        (EXPERIMENTAL) Registers a link matcher, allowing custom link patterns to be matched and handled.
-        @deprecated The link matcher API is now deprecated in favor of the link provider API, see `registerLinkProvider`.
-        Parameters:
-        ▪ regex: RegExp
-        The regular expression to search for, specifically this searches the textContent of the rows. You will want to use \s to match a space ‘ ‘ character for example.
-        ▪ handler: function
-        The callback when the link is called.
-        ▸ (event: MouseEvent, uri: string): void
-        Parameters:
-        Name    Type
-        event   MouseEvent
-        uri     string
-        ▪Optional options: ILinkMatcherOptions
-        Options for the link matcher.
-        Returns: number
-        The ID of the new matcher, this can be used to deregister.
-    */
+    /// **[EXPERIMENTAL]** Registers a link matcher, allowing custom link
+    /// patterns to be matched and handled.
+    ///
+    /// Takes:
+    ///   - `regex`:   The regular expression to search for, specifically this
+    ///                searches the textContent of the rows. You will want to
+    ///                use `\s` to match a space (`' '`) character for example.
+    ///   - `handler`: The callback when the link is called; it's given the
+    ///                mouse event and the URI of the link.
+    ///   - `options`: Options for the link matcher.
+    ///
+    /// Returns the ID of the new matcher; this can be used to
+    /// [deregister](Terminal::deregister_link_matcher) the matcher.
+    ///
+    /// See [`register_regex_link_matcher`] (if the `ext` feature is enabled)
+    /// for a friendlier version of this function.
+    ///
+    /// [`register_regex_link_matcher`]: Terminal::register_regex_link_matcher
+    #[deprecated(
+        since = "4.6.0",
+        note = "The link matcher API is deprecated in favor of the link \
+        provider API. See: `Terminal::register_link_provider`."
+    )]
+    #[wasm_bindgen(method, js_name = registerLinkMatcher)]
+    pub fn register_link_matcher(
+        this: &Terminal,
+        regex: &js_sys::RegExp,
+        handler: &Closure<dyn FnMut(web_sys::MouseEvent, Str)>,
+        options: Option<LinkMatcherOptions>,
+    ) -> u32;
 
-    /*  [TODO]
-        registerMarker
-        ▸ registerMarker(cursorYOffset: number): IMarker | undefined
-        (EXPERIMENTAL) Adds a marker to the normal buffer and returns it. If the alt buffer is active, undefined is returned.
-        Parameters:
-        Name    Type    Description
-        cursorYOffset   number  The y position offset of the marker from the cursor.
-        Returns: IMarker | undefined
-    */
+    /// **[EXPERIMENTAL]** Registers a link provider, allowing a custom parser
+    /// to be used to match and handle links. Multiple link providers can be
+    /// used, they will be asked in the order in which they are registered.
+    ///
+    /// Takes:
+    ///   - `link_provider`: The link provider to use to detect links.
+    ///
+    /// Returns a [`Disposable`] that unregisters the provider.
+    ///
+    /// See [`register_xterm_link_provider`] (if the `ext` feature is enabled)
+    /// for a friendlier version of this function.
+    ///
+    /// [`register_xterm_link_provider`]: Terminal::register_xterm_link_provider
+    #[wasm_bindgen(method, js_name = registerLinkProvider)]
+    pub fn register_link_provider(
+        this: &Terminal,
+        link_provider: LinkProvider,
+    ) -> Disposable;
+
+    /// **[EXPERIMENTAL]** Adds a marker to the normal buffer and returns it.
+    /// If the alt buffer is active, `None` is returned.
+    ///
+    /// Takes:
+    ///   - `cursor_y_offset`: The y position offset of the marker from the
+    ///                        cursor.
+    ///
+    /// See [`track_range`] (if the `ext` feature is enabled) for a way to
+    /// track a range of lines with a pair of markers.
+    ///
+    /// [`track_range`]: Terminal::track_range
+    #[wasm_bindgen(method, js_name = registerMarker)]
+    pub fn register_marker(
+        this: &Terminal,
+        cursor_y_offset: i32,
+    ) -> Option<Marker>;
 
     /// Perform a full reset (RIS, aka ‘\x1bc’).
     #[wasm_bindgen(method, js_name = reset)]
@@ -2323,88 +2503,14 @@ extern "C" {
     #[wasm_bindgen(method, js_name = selectLines)]
     pub fn select_lines(this: &Terminal, start: u32, end: u32);
 
-    /*  [TODO]
-        setOption
-        ▸ setOption(key: “fontFamily”   “termName”  “bellSound”     “wordSeparator”, value: string): void
-        Sets an option on the terminal.
-        Parameters:
-        Name    Type    Description
-        key     “fontFamily” | “termName” | “bellSound” | “wordSeparator”   The option key.
-        value   string  The option value.
-        Returns: void
-
-        ▸ setOption(key: “fontWeight”   “fontWeightBold”, value: null   “normal”    “bold”  “100”   “200”   “300”   “400”   “500”   “600”   “700”   “800”   “900”): void
-        Sets an option on the terminal.
-        Parameters:
-        Name    Type    Description
-        key     “fontWeight” | “fontWeightBold”     The option key.
-        value   null | “normal” | “bold” | “100” | “200” | “300” | “400” | “500” | “600” | “700” | “800” | “900”    The option value.
-        Returns: void
-
-        ▸ setOption(key: “logLevel”, value: LogLevel): void
-        Sets an option on the terminal.
-        Parameters:
-        Name    Type    Description
-        key     “logLevel”  The option key.
-        value   LogLevel    The option value.
-        Returns: void
-
-        ▸ setOption(key: “bellStyle”, value: null   “none”  “visual”    “sound”     “both”): void
-        Sets an option on the terminal.
-        Parameters:
-        Name    Type    Description
-        key     “bellStyle”     The option key.
-        value   null | “none” | “visual” | “sound” | “both”     The option value.
-        Returns: void
-
-        ▸ setOption(key: “cursorStyle”, value: null     “block”     “underline”     “bar”): void
-        Sets an option on the terminal.
-        Parameters:
-        Name    Type    Description
-        key     “cursorStyle”   The option key.
-        value   null | “block” | “underline” | “bar”    The option value.
-        Returns: void
-
-        ▸ setOption(key: “allowTransparency”    “cancelEvents”  “convertEol”    “cursorBlink”   “disableStdin”  “macOptionIsMeta”   “popOnBell”     “rightClickSelectsWord”     “visualBell”    “windowsMode”, value: boolean): void
-        Sets an option on the terminal.
-        Parameters:
-        Name    Type    Description
-        key     “allowTransparency” | “cancelEvents” | “convertEol” | “cursorBlink” | “disableStdin” | “macOptionIsMeta” | “popOnBell” | “rightClickSelectsWord” | “visualBell” | “windowsMode”     The option key.
-        value   boolean     The option value.
-        Returns: void
-
-        ▸ setOption(key: “fontSize”     “letterSpacing”     “lineHeight”    “tabStopWidth”  “scrollback”, value: number): void
-        Sets an option on the terminal.
-        Parameters:
-        Name    Type    Description
-        key     “fontSize” | “letterSpacing” | “lineHeight” | “tabStopWidth” | “scrollback”     The option key.
-        value   number  The option value.
-        Returns: void
-
-        ▸ setOption(key: “theme”, value: ITheme): void
-        Sets an option on the terminal.
-        Parameters:
-        Name    Type    Description
-        key     “theme”     The option key.
-        value   ITheme  The option value.
-        Returns: void
-
-        ▸ setOption(key: “cols”     “rows”, value: number): void
-        Sets an option on the terminal.
-        Parameters:
-        Name    Type    Description
-        key     “cols” | “rows”     The option key.
-        value   number  The option value.
-        Returns: void
-
-        ▸ setOption(key: string, value: any): void
-        Sets an option on the terminal.
-        Parameters:
-        Name    Type    Description
-        key     string  The option key.
-        value   any     The option value.
-        Returns: void
-    */
+    /// Sets an option on the terminal.
+    ///
+    /// Takes:
+    ///   - `key`:   The option's name (i.e. `"fontSize"`); the names are the
+    ///              JS names of the fields of [`TerminalOptions`].
+    ///   - `value`: The option's new value.
+    #[wasm_bindgen(method, js_name = setOption)]
+    pub fn set_option(this: &Terminal, key: &str, value: &JsValue);
 
     // `Option<&Closure<dyn FnMut()>>` can't be passed to JS functions, so we
     // have a version of write with the callback and one without it.
@@ -2445,32 +2551,113 @@ extern "C" {
         callback: &Closure<dyn FnMut()>,
     );
 
-// TODO: registerLinkProvider
-
-// [TODO]
-//   writeUtf8
-//   ▸ writeUtf8(data: Uint8Array, callback?: function): void
-//   Defined in xterm.d.ts:896
-//   Write UTF8 data to the terminal.
-//   deprecated use write instead
-//   Parameters:
-//   ▪ data: Uint8Array
-//   The data to write to the terminal.
-//   ▪Optional callback: function
-//   Optional callback when data was processed.
-//   ▸ (): void
-//   Returns: void
-
-// [TODO]
-//   writeln
-//   ▸ writeln(data: string  Uint8Array, callback?: function): void
-//   Writes data to the terminal, followed by a break line character (\n).
-//   Parameters:
-//   ▪ data: *string     Uint8Array*
-//   The data to write to the terminal. This can either be raw bytes given as Uint8Array from the pty or a string. Raw bytes will always be treated as UTF-8 encoded, string data as UTF-16.
-//   ▪Optional callback: function
-//   Optional callback that fires when the data was processed by the parser.
-//   ▸ (): void
-//   Returns: void
+    /// Writes a string slice to the terminal.
+    ///
+    /// This is identical to [`write`] except that it takes a `&str`, which
+    /// saves callers that have a borrowed string an allocation.
+    ///
+    /// [`write`]: Terminal::write
+    #[wasm_bindgen(method, js_name = write)]
+    pub fn write_str(this: &Terminal, data: &str);
+
+    /// Writes a string slice to the terminal and takes a callback.
+    ///
+    /// This is identical to [`write_with_callback`] except that it takes a
+    /// `&str`.
+    ///
+    /// [`write_with_callback`]: Terminal::write_with_callback
+    #[wasm_bindgen(method, js_name = write)]
+    pub fn write_str_with_callback(
+        this: &Terminal,
+        data: &str,
+        callback: &Closure<dyn FnMut()>,
+    );
+
+    /// Writes raw (UTF-8 encoded) bytes to the terminal.
+    ///
+    /// Takes:
+    ///   - `data`: The data to write to the terminal. Note that xterm.js may
+    ///             hold on to this array until it gets around to parsing it so
+    ///             it should _not_ be a view into wasm memory.
+    ///
+    /// See [`write_bytes`] (if the `ext` feature is enabled) for a version of
+    /// this function that takes a `&[u8]`.
+    ///
+    /// [`write_bytes`]: Terminal::write_bytes
+    #[wasm_bindgen(method, js_name = write)]
+    pub fn write_uint8_array(this: &Terminal, data: &js_sys::Uint8Array);
+
+    /// Writes raw (UTF-8 encoded) bytes to the terminal and takes a callback.
+    ///
+    /// This identical to [`write_uint8_array`] except it also takes a
+    /// callback.
+    ///
+    /// Takes:
+    ///   - `data`:     The data to write to the terminal. As with
+    ///                 [`write_uint8_array`] this should not be a view into
+    ///                 wasm memory.
+    ///   - `callback`: Callback that fires when the data was processed by the
+    ///                 parser.
+    ///
+    /// [`write_uint8_array`]: Terminal::write_uint8_array
+    #[wasm_bindgen(method, js_name = write)]
+    pub fn write_uint8_array_with_callback(
+        this: &Terminal,
+        data: &js_sys::Uint8Array,
+        callback: &Closure<dyn FnMut()>,
+    );
+
+    /// Write UTF8 data to the terminal.
+    ///
+    /// Takes:
+    ///   - `data`:     The data to write to the terminal.
+    ///   - `callback`: Callback that fires when the data was processed by the
+    ///                 parser.
+    #[deprecated(note = "Use `Terminal::write_uint8_array` instead.")]
+    #[wasm_bindgen(method, js_name = writeUtf8)]
+    pub fn write_utf8(
+        this: &Terminal,
+        data: &js_sys::Uint8Array,
+        callback: &Closure<dyn FnMut()>,
+    );
+
+    /// Writes data to the terminal, followed by a break line character (`\n`).
+    ///
+    /// Takes:
+    ///   - `data`: The data to write to the terminal.
+    #[wasm_bindgen(method, js_name = writeln)]
+    pub fn writeln(this: &Terminal, data: Str);
+
+    /// Writes data to the terminal, followed by a break line character (`\n`),
+    /// and takes a callback.
+    ///
+    /// Takes:
+    ///   - `data`:     The data to write to the terminal.
+    ///   - `callback`: Callback that fires when the data was processed by the
+    ///                 parser.
+    #[wasm_bindgen(method, js_name = writeln)]
+    pub fn writeln_with_callback(
+        this: &Terminal,
+        data: Str,
+        callback: &Closure<dyn FnMut()>,
+    );
+
+    /// Writes a string slice to the terminal, followed by a break line
+    /// character (`\n`).
+    ///
+    /// This is identical to [`writeln`] except that it takes a `&str`.
+    ///
+    /// [`writeln`]: Terminal::writeln
+    #[wasm_bindgen(method, js_name = writeln)]
+    pub fn writeln_str(this: &Terminal, data: &str);
 
+    /// Writes raw (UTF-8 encoded) bytes to the terminal, followed by a break
+    /// line character (`\n`).
+    ///
+    /// As with [`write_uint8_array`], `data` should not be a view into wasm
+    /// memory.
+    ///
+    /// [`write_uint8_array`]: Terminal::write_uint8_array
+    #[wasm_bindgen(method, js_name = writeln)]
+    pub fn writeln_uint8_array(this: &Terminal, data: &js_sys::Uint8Array);
 }
@@ -0,0 +1,130 @@
+#![cfg(feature = "ext")]
+
+use js_sys::Function;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+use xterm_js_sys::ext::{
+    object, AddonError, AddonManager, XtermAddon, XtermDisposable,
+};
+use xterm_js_sys::xterm::Terminal;
+
+use std::cell::Cell;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Named(&'static str);
+
+impl XtermDisposable for Named {}
+
+impl XtermAddon for Named {
+    fn activate(&self, _terminal: Terminal) {}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Other(u32);
+
+impl XtermDisposable for Other {}
+
+impl XtermAddon for Other {
+    fn activate(&self, _terminal: Terminal) {}
+}
+
+#[test]
+fn dependencies_are_loaded_first() {
+    let mut addons = AddonManager::new();
+    let _ = addons
+        .register("search", &["snapshot"], Named("search"))
+        .register("fit", &[], Named("fit"))
+        .register("links", &["snapshot", "fit"], Named("links"))
+        .register("snapshot", &[], Other(0));
+
+    assert_eq!(
+        addons.load_order(),
+        Ok(vec!["fit", "snapshot", "search", "links"])
+    );
+}
+
+#[test]
+fn typed_access() {
+    let mut addons = AddonManager::new();
+    let _ = addons
+        .register("a", &[], Named("a"))
+        .register("b", &[], Other(7))
+        .register("c", &[], Named("c"));
+
+    assert_eq!(addons.get::<Named>(), Some(&Named("a")));
+    assert_eq!(addons.get::<Other>(), Some(&Other(7)));
+    assert_eq!(addons.get::<String>(), None);
+
+    assert_eq!(addons.get_named::<Named>("c"), Some(&Named("c")));
+    assert_eq!(addons.get_named::<Other>("c"), None);
+    assert!(!addons.is_loaded("a"));
+}
+
+#[test]
+fn errors() {
+    let mut addons = AddonManager::new();
+    let _ = addons.register("a", &["b"], Named("a"));
+    assert_eq!(
+        addons.load_order(),
+        Err(AddonError::UnknownDependency {
+            addon: "a".to_string(),
+            dependency: "b".to_string(),
+        })
+    );
+
+    let _ = addons
+        .register("b", &["c"], Named("b"))
+        .register("c", &["a"], Named("c"))
+        .register("d", &[], Named("d"));
+    assert_eq!(
+        addons.load_order(),
+        Err(AddonError::Cycle(vec![
+            "a".to_string(),
+            "b".to_string(),
+            "c".to_string(),
+        ]))
+    );
+
+    let _ = addons.register("d", &[], Other(1));
+    assert_eq!(
+        addons.load_order(),
+        Err(AddonError::DuplicateName("d".to_string()))
+    );
+}
+
+#[derive(Debug, Default)]
+struct Counter {
+    activations: Cell<u32>,
+}
+
+impl XtermDisposable for Counter {}
+
+impl XtermAddon for Counter {
+    fn activate(&self, _terminal: Terminal) {
+        self.activations.set(self.activations.get() + 1);
+    }
+}
+
+#[wasm_bindgen_test]
+fn get_returns_the_activated_addon() {
+    // A stand-in for an xterm.js `Terminal` that just activates the addons
+    // it's given.
+    let terminal: Terminal = object! {
+        loadAddon: Function::new_with_args("addon", "addon.activate(this)"),
+    }
+    .unchecked_into();
+
+    let mut addons = AddonManager::new();
+    let _ = addons.register("counter", &[], Counter::default());
+    addons.load(&terminal).unwrap();
+
+    assert_eq!(addons.get::<Counter>().unwrap().activations.get(), 1);
+    assert_eq!(
+        addons
+            .get_named::<Counter>("counter")
+            .unwrap()
+            .activations
+            .get(),
+        1
+    );
+}
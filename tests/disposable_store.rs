@@ -0,0 +1,72 @@
+#![cfg(feature = "ext")]
+
+use xterm_js_sys::ext::{DisposableStore, XtermDisposable};
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+type Log = Rc<RefCell<Vec<&'static str>>>;
+
+struct Logged(&'static str, Log);
+
+impl XtermDisposable for Logged {
+    fn dispose(&self) {
+        self.1.borrow_mut().push(self.0);
+    }
+}
+
+struct Guard(&'static str, Log);
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        self.1.borrow_mut().push(self.0);
+    }
+}
+
+#[test]
+fn disposes_in_reverse_order() {
+    let log = Log::default();
+    let store = DisposableStore::new();
+
+    store.add(Logged("first", log.clone()));
+    store.add_guard(Guard("second", log.clone()));
+    store.add(Logged("third", log.clone()));
+    assert_eq!(store.len(), 3);
+
+    store.clear();
+    assert!(store.is_empty());
+    assert_eq!(*log.borrow(), vec!["third", "second", "first"]);
+
+    // The store is still usable after `clear`...
+    store.add(Logged("fourth", log.clone()));
+    drop(store);
+    assert_eq!(log.borrow().last(), Some(&"fourth"));
+}
+
+#[test]
+fn nested_stores() {
+    let log = Log::default();
+    let parent = DisposableStore::new();
+
+    parent.add(Logged("parent", log.clone()));
+    let child = parent.child();
+    child.add(Logged("child 1", log.clone()));
+    child.add(Logged("child 2", log.clone()));
+
+    child.clear();
+    assert_eq!(*log.borrow(), vec!["child 2", "child 1"]);
+    child.add(Logged("child 3", log.clone()));
+
+    parent.dispose();
+    assert!(parent.is_disposed());
+    assert!(child.is_disposed());
+    assert_eq!(
+        *log.borrow(),
+        vec!["child 2", "child 1", "child 3", "parent"]
+    );
+
+    // ...but not after `dispose`.
+    child.add(Logged("late", log.clone()));
+    assert!(child.is_empty());
+    assert_eq!(log.borrow().last(), Some(&"late"));
+}
@@ -0,0 +1,96 @@
+#![cfg(feature = "ext")]
+
+use xterm_js_sys::ext::{
+    propose_dimensions, FitMeasurements, Padding, MINIMUM_COLS, MINIMUM_ROWS,
+};
+
+fn measurements(parent_width: f64, parent_height: f64) -> FitMeasurements {
+    FitMeasurements {
+        parent_width,
+        parent_height,
+        cell_width: 9.0,
+        cell_height: 17.0,
+        ..FitMeasurements::default()
+    }
+}
+
+#[test]
+fn whole_cells() {
+    assert_eq!(
+        propose_dimensions(&measurements(720.0, 340.0)),
+        Some((80, 20))
+    );
+    assert_eq!(
+        propose_dimensions(&measurements(728.9, 356.9)),
+        Some((80, 20))
+    );
+    assert_eq!(
+        propose_dimensions(&measurements(729.0, 357.0)),
+        Some((81, 21))
+    );
+}
+
+#[test]
+fn padding_and_scrollbar() {
+    let fit = FitMeasurements {
+        padding: Padding {
+            top: 5.0,
+            right: 10.0,
+            bottom: 12.0,
+            left: 8.0,
+        },
+        scrollbar_width: 15.0,
+        ..measurements(753.0, 357.0)
+    };
+
+    // 753 - 8 - 10 - 15 = 720; 357 - 5 - 12 = 340
+    assert_eq!(propose_dimensions(&fit), Some((80, 20)));
+}
+
+#[test]
+fn minimums() {
+    assert_eq!(
+        propose_dimensions(&measurements(0.0, 0.0)),
+        Some((MINIMUM_COLS, MINIMUM_ROWS))
+    );
+    assert_eq!(
+        propose_dimensions(&measurements(-50.0, -50.0)),
+        Some((MINIMUM_COLS, MINIMUM_ROWS))
+    );
+}
+
+#[test]
+fn out_of_range_sizes() {
+    // A parent that's narrower than the padding and scrollbar.
+    let fit = FitMeasurements {
+        padding: Padding {
+            top: 40.0,
+            right: 20.0,
+            bottom: 40.0,
+            left: 20.0,
+        },
+        scrollbar_width: 15.0,
+        ..measurements(30.0, 50.0)
+    };
+    assert_eq!(propose_dimensions(&fit), Some((MINIMUM_COLS, MINIMUM_ROWS)));
+
+    assert_eq!(
+        propose_dimensions(&measurements(f64::NAN, f64::INFINITY)),
+        Some((MINIMUM_COLS, u16::MAX))
+    );
+    assert_eq!(
+        propose_dimensions(&measurements(1e9, 1e9)),
+        Some((u16::MAX, u16::MAX))
+    );
+}
+
+#[test]
+fn unknown_cell_size() {
+    let mut fit = measurements(720.0, 340.0);
+    fit.cell_width = 0.0;
+    assert_eq!(propose_dimensions(&fit), None);
+
+    let mut fit = measurements(720.0, 340.0);
+    fit.cell_height = f64::NAN;
+    assert_eq!(propose_dimensions(&fit), None);
+}
@@ -0,0 +1,196 @@
+#![cfg(feature = "ext")]
+
+use xterm_js_sys::ext::{
+    decode_input, InputDecoder, InputEvent, KeyCode, KeyInput, Modifiers,
+    MouseAction, MouseButton, MouseInput,
+};
+
+fn key(code: KeyCode) -> InputEvent {
+    InputEvent::Key(KeyInput::plain(code))
+}
+
+fn key_with(code: KeyCode, modifiers: Modifiers) -> InputEvent {
+    InputEvent::Key(KeyInput::new(code, modifiers))
+}
+
+const CTRL: Modifiers = Modifiers {
+    ctrl: true,
+    ..Modifiers::NONE
+};
+
+const ALT: Modifiers = Modifiers {
+    alt: true,
+    ..Modifiers::NONE
+};
+
+const SHIFT: Modifiers = Modifiers {
+    shift: true,
+    ..Modifiers::NONE
+};
+
+fn mouse(action: MouseAction, column: u16, row: u16) -> InputEvent {
+    InputEvent::Mouse(MouseInput {
+        action,
+        column,
+        row,
+        modifiers: Modifiers::NONE,
+    })
+}
+
+#[test]
+fn plain_keys() {
+    assert_eq!(
+        decode_input("hé\r\t\x7f"),
+        vec![
+            key(KeyCode::Char('h')),
+            key(KeyCode::Char('é')),
+            key(KeyCode::Enter),
+            key(KeyCode::Tab),
+            key(KeyCode::Backspace),
+        ]
+    );
+
+    assert_eq!(
+        decode_input("\x03\x00\x1b\x1b\x1bx"),
+        vec![
+            key_with(KeyCode::Char('c'), CTRL),
+            key_with(KeyCode::Char(' '), CTRL),
+            key_with(KeyCode::Escape, ALT),
+            key_with(KeyCode::Char('x'), ALT),
+        ]
+    );
+
+    assert_eq!(decode_input("\x1b"), vec![key(KeyCode::Escape)]);
+}
+
+#[test]
+fn csi_and_ss3_keys() {
+    assert_eq!(
+        decode_input("\x1b[A\x1bOB\x1b[H\x1bOF\x1bOP\x1b[Z"),
+        vec![
+            key(KeyCode::Up),
+            key(KeyCode::Down),
+            key(KeyCode::Home),
+            key(KeyCode::End),
+            key(KeyCode::F(1)),
+            key_with(KeyCode::BackTab, SHIFT),
+        ]
+    );
+
+    assert_eq!(
+        decode_input("\x1b[1~\x1b[3~\x1b[5~\x1b[6~\x1b[15~\x1b[24~"),
+        vec![
+            key(KeyCode::Home),
+            key(KeyCode::Delete),
+            key(KeyCode::PageUp),
+            key(KeyCode::PageDown),
+            key(KeyCode::F(5)),
+            key(KeyCode::F(12)),
+        ]
+    );
+}
+
+#[test]
+fn modifiers() {
+    let ctrl_shift = Modifiers {
+        ctrl: true,
+        shift: true,
+        ..Modifiers::NONE
+    };
+
+    assert_eq!(
+        decode_input("\x1b[1;5C\x1b[1;3P\x1b[3;6~"),
+        vec![
+            key_with(KeyCode::Right, CTRL),
+            key_with(KeyCode::F(1), ALT),
+            key_with(KeyCode::Delete, ctrl_shift),
+        ]
+    );
+
+    for param in 1..=16 {
+        assert_eq!(Modifiers::from_param(param).to_param(), param);
+    }
+}
+
+#[test]
+fn mouse_reports() {
+    // X10: button, column and row offset by 32 (and 1-based).
+    assert_eq!(
+        decode_input("\x1b[M !!\x1b[M#\"#"),
+        vec![
+            mouse(MouseAction::Down(MouseButton::Left), 0, 0),
+            mouse(MouseAction::Up(None), 1, 2),
+        ]
+    );
+
+    // UTF-8 (1005): same, but the coordinates can go past 223.
+    assert_eq!(
+        decode_input("\x1b[M!\u{12c}!"),
+        vec![mouse(MouseAction::Down(MouseButton::Middle), 267, 0)]
+    );
+
+    // SGR (1006).
+    assert_eq!(
+        decode_input("\x1b[<0;10;5M\x1b[<0;10;5m\x1b[<34;3;4M\x1b[<65;1;1M"),
+        vec![
+            mouse(MouseAction::Down(MouseButton::Left), 9, 4),
+            mouse(MouseAction::Up(Some(MouseButton::Left)), 9, 4),
+            mouse(MouseAction::Drag(MouseButton::Right), 2, 3),
+            mouse(MouseAction::ScrollDown, 0, 0),
+        ]
+    );
+
+    // URXVT (1015).
+    assert_eq!(
+        decode_input("\x1b[32;300;2M"),
+        vec![mouse(MouseAction::Down(MouseButton::Left), 299, 1)]
+    );
+
+    // Modifiers.
+    assert_eq!(
+        decode_input("\x1b[<16;1;1M"),
+        vec![InputEvent::Mouse(MouseInput {
+            action: MouseAction::Down(MouseButton::Left),
+            column: 0,
+            row: 0,
+            modifiers: CTRL,
+        })]
+    );
+}
+
+#[test]
+fn focus_and_unknown() {
+    assert_eq!(
+        decode_input("\x1b[I\x1b[O\x1b[99x"),
+        vec![
+            InputEvent::FocusGained,
+            InputEvent::FocusLost,
+            InputEvent::Unknown("\x1b[99x".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn bracketed_paste() {
+    assert_eq!(
+        decode_input("a\x1b[200~b\x1b[Ac\r\x1b[201~d"),
+        vec![
+            key(KeyCode::Char('a')),
+            InputEvent::Paste("b\x1b[Ac\r".to_string()),
+            key(KeyCode::Char('d')),
+        ]
+    );
+
+    let mut decoder = InputDecoder::new();
+    assert_eq!(decoder.feed("\x1b[200~one "), vec![]);
+    assert!(decoder.in_paste());
+    assert_eq!(decoder.feed("two"), vec![]);
+    assert_eq!(
+        decoder.feed("\x1b[201~\r"),
+        vec![
+            InputEvent::Paste("one two".to_string()),
+            key(KeyCode::Enter)
+        ]
+    );
+    assert!(!decoder.in_paste());
+}
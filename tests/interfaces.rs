@@ -158,3 +158,267 @@ fn interface_with_extends() {
 
     traitless::inner(&blab)
 }
+
+#[derive(Debug, Clone)]
+struct CountedFrob(std::rc::Rc<()>);
+
+impl RustFrobber for CountedFrob {
+    fn frob(&self, a: u32) -> u32 {
+        a
+    }
+
+    fn quaz(&self, a: u32, b: u16) -> u32 {
+        a + (b as u32)
+    }
+}
+
+#[wasm_bindgen_test]
+fn handles_free_the_rust_value() {
+    let count = std::rc::Rc::new(());
+
+    let handle = CountedFrob(count.clone()).into_js_handle();
+    assert_eq!(handle.quaz(1, 2), 3);
+    assert!(std::rc::Rc::strong_count(&count) > 1);
+    assert!(!handle.is_freed());
+
+    drop(handle);
+    assert_eq!(std::rc::Rc::strong_count(&count), 1);
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[derive(Debug, Clone)]
+    pub type Closer;
+
+    #[wasm_bindgen(structural, method)]
+    pub fn dispose(this: &Closer);
+}
+
+interface! {
+    pub trait RustCloser mirrors Closer {
+        fn dispose(&self);
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CountedCloser(std::rc::Rc<std::cell::Cell<u32>>);
+
+impl RustCloser for CountedCloser {
+    fn dispose(&self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+#[wasm_bindgen_test]
+fn released_handles_are_freed_on_dispose() {
+    let disposed = std::rc::Rc::new(std::cell::Cell::new(0));
+
+    let closer: Closer = CountedCloser(disposed.clone()).into_js();
+    assert_eq!(std::rc::Rc::strong_count(&disposed), 2);
+
+    closer.dispose();
+    assert_eq!(disposed.get(), 1);
+    assert_eq!(std::rc::Rc::strong_count(&disposed), 1);
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[derive(Debug, Clone)]
+    pub type Versioned;
+
+    #[wasm_bindgen(structural, method, getter = version)]
+    pub fn version(this: &Versioned) -> String;
+
+    #[wasm_bindgen(structural, method, getter = major)]
+    pub fn major(this: &Versioned) -> u32;
+
+    #[wasm_bindgen(structural, method)]
+    pub fn bump(this: &Versioned);
+}
+
+interface! {
+    pub trait RustVersioned mirrors Versioned {
+        readonly version: String;
+        get fn major(&self) -> u32;
+        fn bump(&self);
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Counter(std::rc::Rc<std::cell::Cell<u32>>);
+
+impl RustVersioned for Counter {
+    fn version(&self) -> String {
+        format!("{}.0", self.0.get())
+    }
+
+    fn major(&self) -> u32 {
+        self.0.get()
+    }
+
+    fn bump(&self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+#[wasm_bindgen_test]
+fn properties_are_getters() {
+    let versioned: Versioned = Counter::default().to();
+    assert_eq!(versioned.version(), "0.0");
+
+    versioned.bump();
+    assert_eq!(versioned.major(), 1);
+    assert_eq!(versioned.version(), "1.0");
+
+    let value = js_sys::Reflect::get(&versioned, &"version".into()).unwrap();
+    assert_eq!(value.as_string().unwrap(), "1.0");
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[derive(Debug, Clone)]
+    pub type Doubler;
+
+    #[wasm_bindgen(structural, method)]
+    pub fn double(this: &Doubler, a: u32) -> js_sys::Promise;
+}
+
+interface! {
+    pub trait RustDoubler mirrors Doubler {
+        async fn double(&self, a: u32) -> Result<JsValue, JsValue>;
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Twice;
+
+impl RustDoubler for Twice {
+    fn double(
+        &self,
+        a: u32,
+    ) -> xterm_js_sys::ext::LocalBoxFuture<Result<JsValue, JsValue>> {
+        Box::pin(async move { Ok(JsValue::from(a * 2)) })
+    }
+}
+
+#[wasm_bindgen_test]
+async fn async_methods_return_promises() {
+    use wasm_bindgen_futures::JsFuture;
+
+    let doubler: Doubler = Twice.to();
+    let value = JsFuture::from(doubler.double(21)).await.unwrap();
+    assert_eq!(value.as_f64(), Some(42.0));
+
+    // And back again, through the JS impl of the Rust trait:
+    let value = RustDoubler::double(&doubler, 4).await.unwrap();
+    assert_eq!(value.as_f64(), Some(8.0));
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[derive(Debug, Clone)]
+    pub type Greeter;
+
+    #[wasm_bindgen(structural, method)]
+    pub fn greet(this: &Greeter) -> String;
+
+    #[wasm_bindgen(structural, method)]
+    pub fn wave(this: &Greeter) -> u32;
+
+    #[wasm_bindgen(structural, method)]
+    pub fn shout(this: &Greeter) -> String;
+}
+
+interface! {
+    pub trait RustGreeter mirrors Greeter {
+        fn greet(&self) -> String {
+            "hello".to_string()
+        }
+
+        #[optional]
+        fn wave(&self) -> u32;
+
+        /// Shouting is optional too.
+        #[optional]
+        fn shout(&self) -> String;
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Quiet;
+
+impl RustGreeter for Quiet {}
+
+#[derive(Debug, Clone)]
+struct Loud;
+
+impl RustGreeter for Loud {
+    const shout: Option<fn(&Self) -> String> = Some(|_| "HELLO".to_string());
+}
+
+#[wasm_bindgen_test]
+fn optional_members_are_only_set_when_provided() {
+    let has = |obj: &Greeter, name: &str| {
+        js_sys::Reflect::has(obj, &JsValue::from_str(name)).unwrap()
+    };
+
+    let quiet: Greeter = Quiet.to();
+    assert_eq!(quiet.greet(), "hello");
+    assert!(!has(&quiet, "wave"));
+    assert!(!has(&quiet, "shout"));
+
+    let loud: Greeter = Loud.to();
+    assert_eq!(loud.greet(), "hello");
+    assert!(!has(&loud, "wave"));
+    assert_eq!(loud.shout(), "HELLO");
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[derive(Debug, Clone)]
+    pub type Tally;
+
+    #[wasm_bindgen(structural, method)]
+    pub fn add(this: &Tally, n: u32) -> u32;
+
+    #[wasm_bindgen(structural, method)]
+    pub fn total(this: &Tally) -> u32;
+
+    #[wasm_bindgen(structural, method)]
+    pub fn doubled(this: &Tally) -> u32;
+}
+
+interface! {
+    pub trait RustTally mirrors Tally {
+        fn add(&mut self, n: u32) -> u32;
+        fn total(&self) -> u32;
+
+        fn doubled(&self) -> u32 {
+            self.total() * 2
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Sum(u32);
+
+impl RustTally for Sum {
+    fn add(&mut self, n: u32) -> u32 {
+        self.0 += n;
+        self.0
+    }
+
+    fn total(&self) -> u32 {
+        self.0
+    }
+}
+
+#[wasm_bindgen_test]
+fn mutable_methods_and_default_bodies() {
+    let tally: Tally = Sum::default().to();
+
+    assert_eq!(tally.add(2), 2);
+    assert_eq!(tally.add(3), 5);
+    assert_eq!(tally.total(), 5);
+    assert_eq!(tally.doubled(), 10);
+}
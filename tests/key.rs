@@ -0,0 +1,51 @@
+#![cfg(feature = "ext")]
+
+use xterm_js_sys::ext::{KeyCode, KeyLocation};
+
+#[test]
+fn dom_keys() {
+    assert_eq!(KeyCode::from_dom_key("a", false), Some(KeyCode::Char('a')));
+    assert_eq!(KeyCode::from_dom_key("A", true), Some(KeyCode::Char('A')));
+    assert_eq!(KeyCode::from_dom_key("é", false), Some(KeyCode::Char('é')));
+    assert_eq!(KeyCode::from_dom_key("Enter", false), Some(KeyCode::Enter));
+    assert_eq!(KeyCode::from_dom_key("Tab", false), Some(KeyCode::Tab));
+    assert_eq!(KeyCode::from_dom_key("Tab", true), Some(KeyCode::BackTab));
+    assert_eq!(KeyCode::from_dom_key("ArrowUp", false), Some(KeyCode::Up));
+    assert_eq!(KeyCode::from_dom_key("Esc", false), Some(KeyCode::Escape));
+    assert_eq!(KeyCode::from_dom_key("F1", false), Some(KeyCode::F(1)));
+    assert_eq!(KeyCode::from_dom_key("F24", false), Some(KeyCode::F(24)));
+
+    assert_eq!(KeyCode::from_dom_key("F25", false), None);
+    assert_eq!(KeyCode::from_dom_key("Fn", false), None);
+    assert_eq!(KeyCode::from_dom_key("Shift", true), None);
+    assert_eq!(KeyCode::from_dom_key("Unidentified", false), None);
+
+    assert_eq!(KeyLocation::from_dom(0), KeyLocation::Standard);
+    assert_eq!(KeyLocation::from_dom(3), KeyLocation::Numpad);
+}
+
+#[cfg(all(feature = "crossterm-events", any(unix, windows)))]
+#[test]
+fn crossterm_conversion() {
+    use crossterm::event::{
+        KeyCode as CtKeyCode, KeyEvent as CtKeyEvent, KeyModifiers,
+    };
+    use xterm_js_sys::ext::{KeyInput, Modifiers};
+
+    let key = KeyInput::new(
+        KeyCode::Escape,
+        Modifiers {
+            ctrl: true,
+            alt: true,
+            ..Modifiers::NONE
+        },
+    );
+
+    assert_eq!(
+        CtKeyEvent::from(key),
+        CtKeyEvent {
+            code: CtKeyCode::Esc,
+            modifiers: KeyModifiers::CONTROL | KeyModifiers::ALT,
+        }
+    );
+}
@@ -0,0 +1,161 @@
+#![cfg(feature = "ext")]
+
+use xterm_js_sys::ext::{
+    encode_kitty, encode_modify_other_keys, KeyCode, KeyEventType, KeyInput,
+    KeyboardModes, KittyFlags, Modifiers,
+};
+
+const CTRL: Modifiers = Modifiers {
+    ctrl: true,
+    ..Modifiers::NONE
+};
+
+const SHIFT: Modifiers = Modifiers {
+    shift: true,
+    ..Modifiers::NONE
+};
+
+const DISAMBIGUATE: KittyFlags = KittyFlags {
+    disambiguate: true,
+    ..KittyFlags::NONE
+};
+
+fn press(code: KeyCode, modifiers: Modifiers) -> Option<String> {
+    encode_kitty(
+        KeyInput::new(code, modifiers),
+        KeyEventType::Press,
+        DISAMBIGUATE,
+    )
+}
+
+#[test]
+fn kitty_disambiguate() {
+    // Text and unmodified legacy keys are sent as-is.
+    assert_eq!(press(KeyCode::Char('a'), Modifiers::NONE), None);
+    assert_eq!(press(KeyCode::Char('A'), SHIFT), None);
+    assert_eq!(press(KeyCode::Tab, Modifiers::NONE), None);
+    assert_eq!(press(KeyCode::Up, Modifiers::NONE), None);
+
+    // Ctrl+I and Tab are now distinct, as are Escape and Ctrl+Enter.
+    assert_eq!(press(KeyCode::Char('i'), CTRL), Some("\x1b[105;5u".into()));
+    assert_eq!(press(KeyCode::Char('I'), CTRL), Some("\x1b[105;5u".into()));
+    assert_eq!(
+        press(KeyCode::Escape, Modifiers::NONE),
+        Some("\x1b[27u".into())
+    );
+    assert_eq!(press(KeyCode::Enter, CTRL), Some("\x1b[13;5u".into()));
+    assert_eq!(press(KeyCode::BackTab, SHIFT), Some("\x1b[9;2u".into()));
+
+    // Releases aren't reported without the flag.
+    assert_eq!(
+        encode_kitty(
+            KeyInput::new(KeyCode::Escape, Modifiers::NONE),
+            KeyEventType::Release,
+            DISAMBIGUATE,
+        ),
+        None
+    );
+}
+
+#[test]
+fn kitty_all_keys_and_event_types() {
+    let flags = KittyFlags {
+        report_all_keys: true,
+        report_event_types: true,
+        ..KittyFlags::NONE
+    };
+    let encode = |code, event_type| {
+        encode_kitty(KeyInput::plain(code), event_type, flags)
+    };
+
+    assert_eq!(
+        encode(KeyCode::Char('a'), KeyEventType::Press),
+        Some("\x1b[97u".into())
+    );
+    assert_eq!(
+        encode(KeyCode::Char('a'), KeyEventType::Repeat),
+        Some("\x1b[97;1:2u".into())
+    );
+    assert_eq!(
+        encode(KeyCode::Enter, KeyEventType::Release),
+        Some("\x1b[13;1:3u".into())
+    );
+    assert_eq!(
+        encode(KeyCode::Up, KeyEventType::Press),
+        Some("\x1b[A".into())
+    );
+    assert_eq!(
+        encode(KeyCode::F(5), KeyEventType::Press),
+        Some("\x1b[15~".into())
+    );
+}
+
+#[test]
+fn modify_other_keys() {
+    let ctrl_i = KeyInput::new(KeyCode::Char('i'), CTRL);
+    let ctrl_1 = KeyInput::new(KeyCode::Char('1'), CTRL);
+
+    assert_eq!(encode_modify_other_keys(ctrl_i, 0), None);
+    assert_eq!(encode_modify_other_keys(ctrl_i, 1), None);
+    assert_eq!(
+        encode_modify_other_keys(ctrl_i, 2),
+        Some("\x1b[27;5;105~".into())
+    );
+    assert_eq!(
+        encode_modify_other_keys(ctrl_1, 1),
+        Some("\x1b[27;5;49~".into())
+    );
+    assert_eq!(
+        encode_modify_other_keys(KeyInput::new(KeyCode::Enter, CTRL), 1),
+        Some("\x1b[27;5;13~".into())
+    );
+    assert_eq!(
+        encode_modify_other_keys(KeyInput::new(KeyCode::Char('A'), SHIFT), 2),
+        None
+    );
+    assert_eq!(
+        encode_modify_other_keys(KeyInput::plain(KeyCode::Tab), 2),
+        None
+    );
+}
+
+#[test]
+fn negotiation() {
+    let mut modes = KeyboardModes::new();
+    let mut replies = Vec::new();
+    let mut csi =
+        |modes: &mut KeyboardModes, prefix, final_byte, params: &[u32]| {
+            modes.handle_csi(prefix, final_byte, params, |r| {
+                replies.push(r.to_string())
+            })
+        };
+
+    assert!(csi(&mut modes, '?', 'u', &[0]));
+    assert!(csi(&mut modes, '>', 'u', &[1]));
+    assert!(csi(&mut modes, '=', 'u', &[8, 2]));
+    assert!(csi(&mut modes, '?', 'u', &[0]));
+    assert_eq!(modes.kitty_flags().bits(), 9);
+
+    // Kitty takes precedence over `modifyOtherKeys`.
+    assert!(csi(&mut modes, '>', 'm', &[4, 2]));
+    assert_eq!(
+        modes.encode(KeyInput::plain(KeyCode::Char('x')), KeyEventType::Press),
+        Some("\x1b[120u".into())
+    );
+
+    assert!(csi(&mut modes, '<', 'u', &[0]));
+    assert_eq!(modes.kitty_flags(), KittyFlags::NONE);
+    assert_eq!(
+        modes.encode(
+            KeyInput::new(KeyCode::Char('x'), CTRL),
+            KeyEventType::Press
+        ),
+        Some("\x1b[27;5;120~".into())
+    );
+
+    assert!(csi(&mut modes, '?', 'm', &[4]));
+    assert!(!csi(&mut modes, '>', 'm', &[1, 2]));
+    assert_eq!(modes.modify_other_keys(), 2);
+
+    assert_eq!(replies, vec!["\x1b[?0u", "\x1b[?9u", "\x1b[>4;2m"]);
+}
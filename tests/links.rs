@@ -0,0 +1,137 @@
+#![cfg(feature = "ext")]
+
+use js_sys::{Function, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_test::*;
+use xterm_js_sys::ext::{
+    find_file_locations, find_urls, object, FileLocation, XtermLinkProvider,
+};
+use xterm_js_sys::xterm::{LinkProvider, Terminal};
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+fn urls(text: &str) -> Vec<&str> {
+    find_urls(text).into_iter().map(|r| &text[r]).collect()
+}
+
+fn locations(text: &str) -> Vec<(&str, FileLocation)> {
+    find_file_locations(text)
+        .into_iter()
+        .map(|(r, loc)| (&text[r], loc))
+        .collect()
+}
+
+fn loc(path: &str, line: u32, column: Option<u32>) -> FileLocation {
+    FileLocation {
+        path: path.to_string(),
+        line,
+        column,
+    }
+}
+
+#[test]
+fn url_detection() {
+    assert_eq!(
+        urls("see https://xtermjs.org and http://example.com/a?b=c#d"),
+        vec!["https://xtermjs.org", "http://example.com/a?b=c#d"]
+    );
+    assert_eq!(
+        urls("open file:///tmp/out.txt"),
+        vec!["file:///tmp/out.txt"]
+    );
+
+    // Not URLs:
+    assert!(urls("ftp://example.com https:// xhttps://example.com").is_empty());
+}
+
+#[test]
+fn url_trimming() {
+    assert_eq!(
+        urls("Go to https://example.com/docs."),
+        vec!["https://example.com/docs"]
+    );
+    assert_eq!(
+        urls("(see https://en.wikipedia.org/wiki/Rust_(language))"),
+        vec!["https://en.wikipedia.org/wiki/Rust_(language)"]
+    );
+    assert_eq!(
+        urls("<https://example.com/>, 'https://example.com/q'"),
+        vec!["https://example.com/", "https://example.com/q"]
+    );
+}
+
+#[test]
+fn file_location_detection() {
+    assert_eq!(
+        locations("  --> src/main.rs:3:5"),
+        vec![("src/main.rs:3:5", loc("src/main.rs", 3, Some(5)))]
+    );
+    assert_eq!(
+        locations("lib.rs:12: error; src\\a.c:7"),
+        vec![
+            ("lib.rs:12", loc("lib.rs", 12, None)),
+            ("src\\a.c:7", loc("src\\a.c", 7, None)),
+        ]
+    );
+    assert_eq!(
+        locations("/home/ü/x.rs:1:2:"),
+        vec![("/home/ü/x.rs:1:2", loc("/home/ü/x.rs", 1, Some(2)))]
+    );
+
+    // Not file locations:
+    assert!(locations("at 12:30:45 on localhost:8080").is_empty());
+    assert!(locations("main.rs:12abc main.rs: 12").is_empty());
+}
+
+#[test]
+fn file_location_parsing() {
+    assert_eq!(
+        FileLocation::parse("src/ext/link.rs:40"),
+        Some(loc("src/ext/link.rs", 40, None))
+    );
+    assert_eq!(FileLocation::parse("src/ext/link.rs:40 "), None);
+    assert_eq!(FileLocation::parse("nope"), None);
+}
+
+/// Sets its flag when it's dropped.
+struct Provider(Rc<Cell<bool>>);
+
+impl Drop for Provider {
+    fn drop(&mut self) {
+        self.0.set(true);
+    }
+}
+
+impl XtermLinkProvider for Provider {
+    fn provide_links(&self, _: u32, callback: Function) {
+        let _ = callback.call1(&JsValue::NULL, &JsValue::UNDEFINED);
+    }
+}
+
+#[wasm_bindgen_test]
+fn link_providers_are_freed_when_unregistered() {
+    // A stand-in for an xterm.js `Terminal` that records the provider it's
+    // given and whether it was unregistered.
+    let terminal: Terminal = object! {
+        registerLinkProvider: Function::new_with_args(
+            "provider",
+            "this.provider = provider; \
+            return { dispose: () => { this.unregistered = true; } };",
+        ),
+    }
+    .unchecked_into();
+    let get = |key: &str| Reflect::get(&terminal, &key.into()).unwrap();
+
+    let dropped = Rc::new(Cell::new(false));
+    let registration =
+        terminal.register_xterm_link_provider(Provider(dropped.clone()));
+
+    let provider: LinkProvider = get("provider").unchecked_into();
+    provider.provide_links(1, Function::new_no_args("return;"));
+    assert!(!dropped.get());
+
+    drop(registration);
+    assert_eq!(get("unregistered"), JsValue::TRUE);
+    assert!(dropped.get());
+}
@@ -0,0 +1,48 @@
+#![cfg(feature = "ext")]
+
+use xterm_js_sys::ext::{MouseEncoding, MouseTracking, TerminalModes};
+
+#[test]
+fn private_modes() {
+    let mut modes = TerminalModes::default();
+
+    assert!(modes.set_private_mode(2004, true));
+    assert!(modes.set_private_mode(1049, true));
+    assert!(modes.set_private_mode(1, true));
+    assert!(modes.set_private_mode(1004, true));
+    assert!(!modes.set_private_mode(25, false));
+
+    assert_eq!(
+        modes,
+        TerminalModes {
+            application_cursor_keys: true,
+            focus_reporting: true,
+            alternate_screen: true,
+            bracketed_paste: true,
+            ..TerminalModes::DEFAULT
+        }
+    );
+
+    assert!(modes.set_private_mode(1049, false));
+    assert!(!modes.alternate_screen);
+}
+
+#[test]
+fn mouse_modes() {
+    let mut modes = TerminalModes::DEFAULT;
+
+    assert!(modes.set_private_mode(1002, true));
+    assert!(modes.set_private_mode(1006, true));
+    assert_eq!(modes.mouse_tracking, MouseTracking::ButtonEvent);
+    assert_eq!(modes.mouse_encoding, MouseEncoding::Sgr);
+
+    // Turning off a different encoding leaves the current one alone...
+    assert!(modes.set_private_mode(1005, false));
+    assert_eq!(modes.mouse_encoding, MouseEncoding::Sgr);
+    assert!(modes.set_private_mode(1006, false));
+    assert_eq!(modes.mouse_encoding, MouseEncoding::Default);
+
+    // ...but turning off any tracking mode turns off tracking.
+    assert!(modes.set_private_mode(1000, false));
+    assert_eq!(modes.mouse_tracking, MouseTracking::Off);
+}
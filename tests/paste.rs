@@ -0,0 +1,75 @@
+#![cfg(feature = "ext")]
+
+use xterm_js_sys::ext::{ControlCharacters, PastePolicy, TerminalModes};
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+#[test]
+fn strips_control_characters() {
+    let mut policy = PastePolicy::new();
+
+    assert_eq!(
+        policy.sanitize("ls\x1b[201~; rm -rf ~\x07\u{9b}\x7f\tx"),
+        Some("ls[201~; rm -rf ~\tx".to_string())
+    );
+}
+
+#[test]
+fn escapes_control_characters() {
+    let mut policy = PastePolicy::new();
+    policy.control_characters = ControlCharacters::Escape;
+
+    assert_eq!(
+        policy.sanitize("a\x1b[201~b\x00\x7f\u{9b}"),
+        Some("a^[[201~b^@^?\\u{9B}".to_string())
+    );
+}
+
+#[test]
+fn newlines() {
+    let mut policy = PastePolicy::new();
+    assert_eq!(
+        policy.sanitize("a\r\nb\nc\rd"),
+        Some("a\rb\rc\rd".to_string())
+    );
+
+    policy.normalize_newlines = false;
+    assert_eq!(policy.sanitize("a\r\nb\n"), Some("a\r\nb\n".to_string()));
+}
+
+#[test]
+fn multiline_confirmation() {
+    let asked = Rc::new(Cell::new(0));
+    let counter = asked.clone();
+
+    let mut policy = PastePolicy::new();
+    let _ = policy.on_multiline(move |text| {
+        counter.set(counter.get() + 1);
+        !text.ends_with('\r')
+    });
+
+    assert_eq!(policy.sanitize("echo hi"), Some("echo hi".to_string()));
+    assert_eq!(asked.get(), 0);
+
+    assert_eq!(policy.sanitize("echo hi\n"), None);
+    assert_eq!(policy.sanitize("one\ntwo"), Some("one\rtwo".to_string()));
+    assert_eq!(asked.get(), 2);
+}
+
+#[test]
+fn bracketed_paste() {
+    let mut policy = PastePolicy::new();
+    let mut modes = TerminalModes::DEFAULT;
+
+    assert_eq!(
+        policy.prepare("a\x1b[201~", &modes),
+        Some("a[201~".to_string())
+    );
+
+    modes.bracketed_paste = true;
+    assert_eq!(
+        policy.prepare("a\x1b[201~", &modes),
+        Some("\x1b[200~a[201~\x1b[201~".to_string())
+    );
+}
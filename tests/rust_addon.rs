@@ -0,0 +1,80 @@
+#![cfg(feature = "ext")]
+
+use js_sys::Function;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+use xterm_js_sys::ext::{object, AddonContext, RustAddon};
+use xterm_js_sys::xterm::Terminal;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+type Log = Rc<RefCell<Vec<&'static str>>>;
+
+struct Guard(&'static str, Log);
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        self.1.borrow_mut().push(self.0);
+    }
+}
+
+struct Tracker {
+    log: Log,
+    activations: u32,
+}
+
+impl RustAddon for Tracker {
+    fn activate(&mut self, ctx: &AddonContext) {
+        self.activations += 1;
+        ctx.track(Guard("registration", self.log.clone()));
+    }
+
+    fn dispose(&mut self) {
+        self.log.borrow_mut().push("dispose");
+    }
+}
+
+/// A stand-in for an xterm.js `Terminal` that just activates the addons it's
+/// given.
+fn fake_terminal() -> Terminal {
+    object! {
+        loadAddon: Function::new_with_args("addon", "addon.activate(this)"),
+    }
+    .unchecked_into()
+}
+
+#[wasm_bindgen_test]
+fn registrations_are_disposed_with_the_addon() {
+    let log = Log::default();
+    let terminal = fake_terminal();
+
+    let handle = terminal.load_rust_addon(Tracker {
+        log: log.clone(),
+        activations: 0,
+    });
+    assert_eq!(handle.addon().activations, 1);
+    assert!(!handle.is_disposed());
+    assert!(log.borrow().is_empty());
+
+    handle.unload();
+    assert_eq!(*log.borrow(), vec!["dispose", "registration"]);
+}
+
+#[wasm_bindgen_test]
+fn addons_are_only_disposed_once() {
+    let log = Log::default();
+    let terminal = fake_terminal();
+
+    let handle = terminal.load_rust_addon(Tracker {
+        log: log.clone(),
+        activations: 0,
+    });
+
+    // As xterm.js does when the terminal is disposed of:
+    handle.js_addon().dispose();
+    assert!(handle.is_disposed());
+
+    drop(handle);
+    assert_eq!(*log.borrow(), vec!["dispose", "registration"]);
+}
@@ -0,0 +1,153 @@
+#![cfg(feature = "ext")]
+
+use js_sys::Object;
+use wasm_bindgen_test::*;
+use xterm_js_sys::ext::{
+    BufferSnapshot, CellColor, CellStyle, MouseEncoding, MouseTracking,
+    SerializedSession, SnapshotCell, SnapshotLine, TerminalModes,
+};
+
+fn line(
+    text: &str,
+    style: CellStyle,
+    cols: usize,
+    wrapped: bool,
+) -> SnapshotLine {
+    let mut cells: Vec<SnapshotCell> = text
+        .chars()
+        .map(|c| SnapshotCell {
+            chars: c.to_string(),
+            width: 1,
+            style,
+        })
+        .collect();
+    cells.resize(
+        cols,
+        SnapshotCell {
+            chars: String::new(),
+            width: 1,
+            style: CellStyle::DEFAULT,
+        },
+    );
+
+    SnapshotLine { cells, wrapped }
+}
+
+#[test]
+fn plain_lines() {
+    let snapshot = BufferSnapshot {
+        lines: vec![
+            line("$ ls", CellStyle::DEFAULT, 10, false),
+            line("", CellStyle::DEFAULT, 10, false),
+            line("a  b", CellStyle::DEFAULT, 10, false),
+        ],
+        cursor_x: 4,
+        cursor_y: 2,
+    };
+
+    assert_eq!(snapshot.to_ansi(), "$ ls\r\n\r\na  b\x1b[3;5H");
+}
+
+#[test]
+fn wrapped_lines_keep_their_blanks() {
+    let snapshot = BufferSnapshot {
+        lines: vec![
+            line("abc", CellStyle::DEFAULT, 5, false),
+            line("de", CellStyle::DEFAULT, 5, true),
+        ],
+        cursor_x: 2,
+        cursor_y: 1,
+    };
+
+    assert_eq!(snapshot.to_ansi(), "abc  de\x1b[2;3H");
+}
+
+#[test]
+fn styles() {
+    let red_bold = CellStyle {
+        fg: CellColor::Palette(1),
+        bold: true,
+        ..CellStyle::DEFAULT
+    };
+    let bright_on_rgb = CellStyle {
+        fg: CellColor::Palette(12),
+        bg: CellColor::Rgb(0, 128, 255),
+        ..CellStyle::DEFAULT
+    };
+    let indexed_inverse = CellStyle {
+        bg: CellColor::Palette(200),
+        inverse: true,
+        ..CellStyle::DEFAULT
+    };
+
+    let mut first = line("ab", red_bold, 4, false);
+    first.cells[1].style = bright_on_rgb;
+    let snapshot = BufferSnapshot {
+        lines: vec![first, line("c", indexed_inverse, 4, false)],
+        cursor_x: 0,
+        cursor_y: 0,
+    };
+
+    assert_eq!(
+        snapshot.to_ansi(),
+        "\x1b[0;1;31ma\x1b[0;94;48;2;0;128;255mb\x1b[0m\r\n\
+        \x1b[0;7;48;5;200mc\x1b[0m\x1b[1;1H"
+    );
+}
+
+#[test]
+fn wide_characters() {
+    let mut wide = line("", CellStyle::DEFAULT, 4, false);
+    wide.cells[0].chars = "漢".to_string();
+    wide.cells[0].width = 2;
+    wide.cells[1].width = 0;
+    wide.cells[2].chars = "x".to_string();
+
+    let snapshot = BufferSnapshot {
+        lines: vec![wide],
+        cursor_x: 3,
+        cursor_y: 0,
+    };
+
+    assert_eq!(snapshot.to_ansi(), "漢x\x1b[1;4H");
+}
+
+#[test]
+fn modes() {
+    assert_eq!(TerminalModes::DEFAULT.to_ansi(), "");
+
+    let modes = TerminalModes {
+        application_cursor_keys: true,
+        mouse_tracking: MouseTracking::ButtonEvent,
+        mouse_encoding: MouseEncoding::Sgr,
+        alternate_screen: true,
+        bracketed_paste: true,
+        ..TerminalModes::DEFAULT
+    };
+    assert_eq!(modes.to_ansi(), "\x1b[?1h\x1b[?1002h\x1b[?1006h\x1b[?2004h");
+}
+
+#[wasm_bindgen_test]
+fn session_sizes_are_range_checked() {
+    let session = SerializedSession {
+        data: "$ ls".to_string(),
+        cols: 80,
+        rows: 24,
+        options: Object::new(),
+    };
+    let decoded = SerializedSession::from_json(&session.to_json()).unwrap();
+    assert_eq!((decoded.cols, decoded.rows), (80, 24));
+
+    let with_size = |cols: &str, rows: &str| {
+        SerializedSession::from_json(&format!(
+            r#"{{"data":"","cols":{},"rows":{},"options":{{}}}}"#,
+            cols, rows,
+        ))
+    };
+    assert!(with_size("80", "24").is_some());
+    assert!(with_size("80", "0").is_none());
+    assert!(with_size("-1", "24").is_none());
+    assert!(with_size("80.5", "24").is_none());
+    assert!(with_size("65536", "24").is_none());
+    assert!(with_size("1e400", "24").is_none());
+}
@@ -0,0 +1,118 @@
+#![cfg(feature = "ext")]
+
+use wasm_bindgen::convert::{FromWasmAbi, IntoWasmAbi};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use xterm_js_sys::ext::{ts_type, TsDeclarations, XtermAddon, XtermLink};
+use xterm_js_sys::interface;
+use xterm_js_sys::xterm::{Link, TerminalAddon};
+
+pub trait IntoJsInterface<Interface: FromWasmAbi + IntoWasmAbi + JsCast> {
+    fn to(self) -> Interface;
+    fn by_ref(&self) -> Interface;
+}
+
+#[test]
+fn types() {
+    assert_eq!(ts_type("u32"), "number");
+    assert_eq!(ts_type("i64"), "bigint");
+    assert_eq!(ts_type("bool"), "boolean");
+    assert_eq!(ts_type("Str"), "string");
+    assert_eq!(ts_type("& 'a str"), "string");
+    assert_eq!(ts_type("()"), "void");
+    assert_eq!(ts_type("wasm_bindgen :: JsValue"), "any");
+
+    assert_eq!(ts_type("Option<String>"), "string | undefined");
+    assert_eq!(ts_type("Result<u16, JsValue>"), "number");
+    assert_eq!(ts_type("Vec<Option<bool>>"), "(boolean | undefined)[]");
+    assert_eq!(ts_type("Box<[u8]>"), "Uint8Array");
+    assert_eq!(ts_type("&[f64]"), "Float64Array");
+    assert_eq!(ts_type("Vec<String>"), "string[]");
+
+    assert_eq!(ts_type("crate::xterm::BufferRange"), "IBufferRange");
+    assert_eq!(ts_type("Terminal"), "Terminal");
+    assert_eq!(ts_type("ResizeEventData"), "{ cols: number, rows: number }");
+    assert_eq!(ts_type("web_sys::HtmlElement"), "HTMLElement");
+    assert_eq!(ts_type("MouseEvent"), "MouseEvent");
+
+    assert_eq!(ts_type("Box<dyn Fn(u32)>"), "any");
+    assert_eq!(ts_type("(u32, u32)"), "any");
+}
+
+#[test]
+fn xterm_interfaces() {
+    assert_eq!(
+        <TerminalAddon as XtermAddon>::ts_declaration().to_string(),
+        "export interface ITerminalAddon extends IDisposable {\n    \
+            activate(terminal: Terminal): void;\n\
+        }\n"
+    );
+
+    assert_eq!(
+        <Link as XtermLink>::ts_declaration().to_string(),
+        "export interface ILink {\n    \
+            readonly range: IBufferRange;\n    \
+            readonly text: string;\n    \
+            activate(event: MouseEvent, text: string): void;\n    \
+            hover?(event: MouseEvent, text: string): void;\n    \
+            leave?(event: MouseEvent, text: string): void;\n\
+        }\n"
+    );
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[derive(Debug, Clone)]
+    pub type Highlighter;
+
+    #[wasm_bindgen(structural, method, getter = name)]
+    pub fn name(this: &Highlighter) -> String;
+
+    #[wasm_bindgen(structural, method)]
+    pub fn highlight(
+        this: &Highlighter,
+        line: String,
+        cols: Option<u16>,
+    ) -> Vec<u32>;
+
+    #[wasm_bindgen(structural, method)]
+    pub fn load(this: &Highlighter, url: String) -> js_sys::Promise;
+
+    #[wasm_bindgen(structural, method)]
+    pub fn reset(this: &Highlighter);
+}
+
+interface! {
+    pub trait RustHighlighter mirrors Highlighter {
+        readonly name: String;
+        fn highlight(&self, line: String, cols: Option<u16>) -> Vec<u32>;
+        async fn load(&self, url: String) -> Result<JsValue, JsValue>;
+
+        #[optional]
+        fn reset(&self);
+    }
+}
+
+#[test]
+fn declarations() {
+    let mut decls = TsDeclarations::new();
+    let _ = decls
+        .add(<Highlighter as RustHighlighter>::ts_declaration())
+        .add(<TerminalAddon as XtermAddon>::ts_declaration());
+
+    assert_eq!(
+        decls.to_string(),
+        "import { IDisposable, Terminal } from 'xterm';\n\
+        \n\
+        export interface Highlighter {\n    \
+            readonly name: string;\n    \
+            highlight(line: string, cols: number | undefined): Uint32Array;\n    \
+            load(url: string): Promise<any>;\n    \
+            reset?(): void;\n\
+        }\n\
+        \n\
+        export interface ITerminalAddon extends IDisposable {\n    \
+            activate(terminal: Terminal): void;\n\
+        }\n"
+    );
+}
@@ -0,0 +1,84 @@
+#![cfg(feature = "macros")]
+
+use wasm_bindgen::convert::{FromWasmAbi, IntoWasmAbi};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+use xterm_js_sys::ext::xterm_interface;
+
+// Same hack as in `interfaces.rs`.
+pub trait IntoJsInterface<Interface: FromWasmAbi + IntoWasmAbi + JsCast> {
+    fn to(self) -> Interface;
+    fn by_ref(&self) -> Interface;
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[derive(Debug, Clone)]
+    pub type Named;
+
+    #[wasm_bindgen(structural, method, getter = name)]
+    pub fn name(this: &Named) -> String;
+
+    #[derive(Debug, Clone)]
+    #[wasm_bindgen(extends = Named)]
+    pub type Counter;
+
+    #[wasm_bindgen(structural, method)]
+    pub fn bump(this: &Counter, by: u32) -> u32;
+
+    #[wasm_bindgen(structural, method)]
+    pub fn reset(this: &Counter);
+
+    #[wasm_bindgen(structural, method)]
+    pub fn describe(this: &Counter) -> String;
+}
+
+#[xterm_interface(mirrors = Named)]
+pub trait RustNamed {
+    /// The name of the thing.
+    #[getter]
+    fn name(&self) -> String;
+}
+
+#[xterm_interface(mirrors = Counter, extends(Named = RustNamed))]
+pub trait RustCounter {
+    fn bump(&mut self, by: u32) -> u32;
+
+    #[optional]
+    fn reset(&self);
+
+    fn describe(&self) -> String {
+        format!("a counter named {}", self.name())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Clicks(u32);
+
+impl RustNamed for Clicks {
+    fn name(&self) -> String {
+        "clicks".to_string()
+    }
+}
+
+impl RustCounter for Clicks {
+    fn bump(&mut self, by: u32) -> u32 {
+        self.0 += by;
+        self.0
+    }
+}
+
+#[wasm_bindgen_test]
+fn attribute_interfaces() {
+    let counter: Counter = Clicks::default().to();
+
+    assert_eq!(counter.bump(1), 1);
+    assert_eq!(counter.bump(2), 3);
+    assert_eq!(counter.name(), "clicks");
+    assert_eq!(counter.describe(), "a counter named clicks");
+
+    let has_reset =
+        js_sys::Reflect::has(&counter, &JsValue::from_str("reset")).unwrap();
+    assert!(!has_reset);
+}